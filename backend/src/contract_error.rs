@@ -0,0 +1,104 @@
+// src/contract_error.rs
+use serde::Serialize;
+
+/// An error returned by the asset custody contract, decoded from CLI output.
+///
+/// Codes mirror `CustodyError` and the per-subsystem error enums in
+/// `smart_contracts/asset_custody/src/lib.rs`, which share one code sequence.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContractError {
+    pub code: u32,
+    pub name: &'static str,
+    pub message: &'static str,
+}
+
+const CUSTODY_ERRORS: &[(u32, &str, &str)] = &[
+    (1, "AccountNotFound", "Custody account not found"),
+    (2, "AccountAlreadyExists", "Custody account already exists"),
+    (3, "AccountInactive", "Custody account is not active"),
+    (4, "InvalidAmount", "Amount must be positive"),
+    (5, "InsufficientBalance", "Insufficient balance for withdrawal"),
+    (6, "ThresholdTooLow", "Threshold must be at least 2 and above every single signer weight"),
+    (7, "NotEnoughSigners", "Signers cannot reach the required threshold"),
+    (8, "DuplicateSigner", "Signer list contains duplicates"),
+    (9, "NotASigner", "Address is not a signer of this custody account"),
+    (10, "InsufficientSignatures", "Not enough signers authorized the call"),
+    (11, "NotAuthorizedToPropose", "Only a proposer can propose withdrawals"),
+    (12, "ProposalNotFound", "Withdrawal proposal not found"),
+    (13, "ProposalNotPending", "Withdrawal proposal is no longer pending"),
+    (14, "AlreadyApproved", "Proposal already approved by this signer"),
+    (15, "NotApproved", "Proposal not approved by this signer"),
+    (16, "InsufficientApprovals", "Proposal does not have enough approvals"),
+    (17, "InvalidTtl", "TTL must be positive, within the network maximum and at least its threshold"),
+    (18, "WithdrawalNotQueued", "Withdrawal has not been queued yet"),
+    (19, "WithdrawalLocked", "Withdrawal is still inside its time-lock window"),
+    (20, "WithdrawalDelayActive", "Account has a withdrawal delay; queue the withdrawal instead"),
+    (21, "NotAuthorizedToCancel", "Only the proposer, an approver or an admin can cancel withdrawals"),
+    (22, "InvalidVelocityLimit", "Velocity limits need a positive period and a non-negative cap"),
+    (23, "VelocityLimitExceeded", "Withdrawal exceeds the account's velocity limit"),
+    (24, "DestinationNotAllowed", "Destination is not on the account's allowlist"),
+    (25, "DestinationNotActive", "Destination is allowlisted but not active yet"),
+    (26, "NotInitialized", "Contract admin set is missing"),
+    (27, "AlreadyInitialized", "Reserved; the admin set is fixed at deployment"),
+    (28, "NotAnAdmin", "Address is not a contract admin"),
+    (29, "UnsupportedSchemaVersion", "Stored schema is newer than this contract code"),
+    (30, "ContractPaused", "Contract is paused; outflows are blocked"),
+    (31, "ContractNotPaused", "Contract is not paused"),
+    (32, "InsuranceNotConfigured", "Insurance has not been configured"),
+    (33, "InvalidInsuranceConfig", "Invalid insurance committee or premium terms"),
+    (34, "NotInsured", "Account has no insurance coverage for this token"),
+    (35, "NotOnCommittee", "Address is not on the insurance committee"),
+    (36, "CoverageInactive", "Insurance coverage is not active; premium unpaid"),
+    (37, "CoverageExceeded", "Claim exceeds the remaining coverage"),
+    (38, "PremiumNotDue", "Insurance premium is not due yet"),
+    (39, "InsufficientPool", "Insurance pool cannot cover the claim"),
+    (40, "ClaimNotFound", "Insurance claim not found"),
+    (41, "ClaimNotPending", "Insurance claim has already been decided"),
+    (42, "InvalidGuardianConfig", "Invalid guardian set or threshold"),
+    (43, "NotAGuardian", "Address is not a guardian of this custody account"),
+    (44, "RecoveryNotFound", "No recovery is in progress"),
+    (45, "RecoveryInProgress", "A recovery is already in progress"),
+    (46, "RecoveryLocked", "Recovery is still inside its challenge period"),
+    (47, "InvalidSignerWeight", "Signer weights must be positive and below the threshold"),
+    (48, "MissingRole", "Address lacks the role this action requires"),
+    (49, "InvalidApprovalTier", "Approval tiers must be ascending and reachable by the signers"),
+    (50, "SameVault", "Source and destination vault must differ"),
+    (51, "InvalidTimeout", "Escrow timeout must be positive"),
+    (52, "EscrowNotFound", "Escrow not found"),
+    (53, "EscrowNotLocked", "Escrow has already been claimed or refunded"),
+    (54, "EscrowExpired", "Escrow has expired and can only be refunded"),
+    (55, "EscrowNotExpired", "Escrow cannot be refunded before it expires"),
+    (56, "InvalidPreimage", "Preimage does not match the escrow hashlock"),
+    (57, "InvalidSchedule", "Vesting must satisfy start <= cliff <= end with start < end"),
+    (58, "VestingNotFound", "Vesting schedule not found"),
+    (59, "NothingToClaim", "Nothing has vested since the last claim"),
+    (60, "VestingRevoked", "Vesting schedule has already been revoked"),
+    (61, "InvalidInheritanceConfig", "Heir must differ from the owner; dormancy and grace periods must be positive"),
+    (62, "AccountNotDormant", "Account has seen activity within its dormancy period"),
+    (63, "InheritanceInProgress", "An inheritance claim is already in progress"),
+    (64, "InheritanceNotFound", "No inheritance claim in progress"),
+    (65, "InheritanceLocked", "Inheritance is still inside its grace period"),
+    (66, "InvalidOracleConfig", "A price oracle needs a positive maximum price age"),
+    (67, "PriceUnavailable", "Price oracle has no valid price for the token"),
+    (68, "PriceStale", "Price oracle quote is older than the allowed age"),
+    (69, "PriceOverflow", "Price oracle quote is too large to value the amount"),
+];
+
+/// Look for `Error(Contract, #<code>)` in simulation/CLI output and decode it.
+pub fn decode_contract_error(output: &str) -> Option<ContractError> {
+    const MARKER: &str = "Error(Contract, #";
+
+    let start = output.find(MARKER)? + MARKER.len();
+    let digits: String = output[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let code: u32 = digits.parse().ok()?;
+
+    let (_, name, message) = CUSTODY_ERRORS.iter().find(|(c, _, _)| *c == code)?;
+    Some(ContractError {
+        code,
+        name,
+        message,
+    })
+}
@@ -1,20 +1,21 @@
 use axum::{
-    extract::{Json, Path},
-    http::StatusCode,
+    extract::Json,
     response::IntoResponse,
     routing::{get, post},
     Router,
 };
 use axum::response::Json as AxumJson;
 use dotenvy::dotenv;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
 use std::{env, net::SocketAddr};
 use tokio::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 
 mod cli;
+mod contract_error;
 use cli::run_cli;
+use contract_error::decode_contract_error;
 
 #[derive(Deserialize)]
 struct BuildTxReq {
@@ -83,7 +84,7 @@ async fn build_tx(Json(req): Json<BuildTxReq>) -> impl IntoResponse {
             }
             AxumJson(json!({ "ok": true, "xdr": stdout.trim() }))
         }
-        Err(e) => AxumJson(json!({ "ok": false, "error": format!("{}", e) })),
+        Err(e) => AxumJson(cli_error_body(&e)),
     }
 }
 
@@ -91,25 +92,30 @@ async fn build_tx(Json(req): Json<BuildTxReq>) -> impl IntoResponse {
 async fn submit_tx(Json(req): Json<SubmitTxReq>) -> impl IntoResponse {
     let rpc = env::var("RPC_URL").unwrap_or_else(|_| "https://soroban-testnet.stellar.org".into());
 
-    let args = vec![
+    let args = [
         "tx", "send",
         "--rpc-url", &rpc,
         "--xdr", &req.signed_xdr,
     ];
 
-    let args_ref: Vec<&str> = args.iter().map(|s| *s).collect();
-
-    match run_cli(&args_ref).await {
+    match run_cli(&args).await {
         Ok((stdout, stderr)) => {
             if !stderr.trim().is_empty() {
                 tracing::warn!("stderr: {}", stderr);
             }
             AxumJson(json!({ "ok": true, "tx_hash": stdout.trim() }))
         }
-        Err(e) => AxumJson(json!({ "ok": false, "error": format!("{}", e) })),
+        Err(e) => AxumJson(cli_error_body(&e)),
     }
 }
 
+/// Error response for a failed CLI call, with the contract error decoded when present.
+fn cli_error_body(e: &anyhow::Error) -> serde_json::Value {
+    let error = format!("{}", e);
+    let contract_error = decode_contract_error(&error);
+    json!({ "ok": false, "error": error, "contract_error": contract_error })
+}
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     dotenv().ok();
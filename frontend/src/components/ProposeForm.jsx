@@ -36,7 +36,12 @@ export default function ProposalForm({ walletAddress }) {
         "create_custody_account",
         {
           owner: walletAddress,
+          signers: {
+            [walletAddress]: 1,
+            [import.meta.env.VITE_ADMIN_WALLET]: 1,
+          },
           required_signatures: 2,
+          withdrawal_delay: 0,
           insurance: true,
         }
       );
@@ -97,7 +97,7 @@ export async function getBalance(publicKey) {
 /* -------------------------
    Request unsigned XDR from backend
    - backend should return { ok: true, xdr: "<unsigned_xdr>" }
-   - or { ok: false, error: "reason" }
+   - or { ok: false, error: "reason", contract_error: { code, name, message } | null }
    ------------------------- */
 export async function requestUnsignedXDR(fnName, params = {}) {
   const url = `${BACKEND_URL.replace(/\/$/, "")}/build_tx`;
@@ -117,7 +117,7 @@ export async function requestUnsignedXDR(fnName, params = {}) {
 
     const body = await res.json();
     if (!body || body.ok !== true || !body.xdr) {
-      throw new Error(body?.error || "Backend did not return an unsigned XDR");
+      throw new Error(body?.contract_error?.message || body?.error || "Backend did not return an unsigned XDR");
     }
 
     return body.xdr;
@@ -197,7 +197,7 @@ export async function submitSignedXDR(signedXdr) {
 
     const body = await res.json();
     if (!body || body.ok !== true) {
-      throw new Error(body?.error || "Backend failed to submit signed transaction.");
+      throw new Error(body?.contract_error?.message || body?.error || "Backend failed to submit signed transaction.");
     }
 
     return body.tx_hash;
@@ -1,6 +1,8 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, symbol_short, Address, Env, Symbol,
+    contract, contractclient, contracterror, contractimpl, contracttype, log, symbol_short, token,
+    vec, Address, Bytes, BytesN, Env, Error, IntoVal, Map, Symbol, Val, Vec,
 };
 
 // ----------------------------------------------------------
@@ -10,22 +12,460 @@ use soroban_sdk::{
 #[contracttype]
 #[derive(Clone)]
 pub struct CustodyAccount {
+    pub id: u64,
     pub owner: Address,
-    pub balance: i128,
+    /// Balance held per token contract address.
+    pub balances: Map<Address, i128>,
+    /// Signer addresses mapped to their weight.
+    pub signers: Map<Address, u32>,
+    /// Total signer weight needed to approve a withdrawal or policy change.
     pub required_signatures: u32,
+    /// Cooling-off window, in seconds, between a withdrawal reaching its
+    /// approval threshold and becoming executable.
+    pub withdrawal_delay: u64,
+    /// Shorter `withdrawal_delay` that replaces the current one at
+    /// `withdrawal_delay_effective_at`.
+    pub pending_withdrawal_delay: Option<u64>,
+    pub withdrawal_delay_effective_at: u64,
+    /// Stricter approval rules for larger withdrawals, by ascending
+    /// `min_amount`.
+    pub approval_tiers: Vec<ApprovalTier>,
+    /// Rolling spend caps applied to every withdrawal.
+    pub velocity_limits: Vec<VelocityLimit>,
+    /// SEP-40 price feed that turns amounts into USD for the approval tiers
+    /// and velocity limits; without one they use raw token units.
+    pub price_oracle: Option<Address>,
+    /// Oldest price, in seconds, the account accepts from its oracle.
+    pub max_price_age: u64,
+    /// Recent withdrawals, kept as long as the longest velocity period.
+    pub spend_log: Vec<SpendRecord>,
+    /// Withdrawal destinations mapped to the timestamp they become usable.
+    pub allowlist: Map<Address, u64>,
+    /// Seconds a newly allowlisted destination waits before it is usable.
+    pub allowlist_delay: u64,
+    /// Shorter `allowlist_delay` that replaces the current one at
+    /// `allowlist_delay_effective_at`.
+    pub pending_allowlist_delay: Option<u64>,
+    pub allowlist_delay_effective_at: u64,
     pub is_insured: bool,
+    /// Insurance coverage per token; only insured accounts get coverage.
+    pub coverage: Map<Address, Coverage>,
+    /// Roles granted to addresses other than the owner, who implicitly
+    /// holds every role.
+    pub roles: Map<Address, Vec<Role>>,
+    /// Addresses that can jointly move the account to a new owner.
+    pub guardians: Vec<Address>,
+    pub guardian_threshold: u32,
+    /// Seconds the current owner has to cancel a started recovery.
+    pub recovery_period: u64,
+    /// Ledger timestamp of the last owner deposit, outflow or heartbeat.
+    pub last_activity: u64,
+    /// Address that can claim the account once it has been dormant for
+    /// `dormancy_period` seconds.
+    pub heir: Option<Address>,
+    pub dormancy_period: u64,
+    /// Seconds between the heir's claim and the hand-over, during which any
+    /// activity defeats the claim.
+    pub inheritance_grace: u64,
     pub is_active: bool,
 }
 
+/// Duties an address can be granted inside a custody account.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// Start withdrawals.
+    Proposer,
+    /// Count toward signer quorums and approve withdrawals.
+    Approver,
+    /// Read-only access to the account, balance and proposal views.
+    Auditor,
+    /// Change account policy, signers and roles.
+    Admin,
+}
+
+/// An ownership recovery started by the guardians.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingRecovery {
+    pub new_owner: Address,
+    pub guardians: Vec<Address>,
+    pub executable_at: u64,
+}
+
+/// A claim by the heir on a dormant account.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingInheritance {
+    pub heir: Address,
+    pub started_at: u64,
+    pub executable_at: u64,
+}
+
+/// Insurance coverage of one token held by an insured account.
+///
+/// Coverage is active while `paid_until` is in the future; claims may pay
+/// out at most `cap - claimed` over the life of the coverage.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coverage {
+    pub cap: i128,
+    pub paid_until: u64,
+    pub claimed: i128,
+}
+
+/// Contract-wide insurance parameters, set by the admins.
+///
+/// Each premium costs `premium_bps` of the coverage cap and keeps coverage
+/// active for `period` seconds. Claims need `threshold` committee members.
+#[contracttype]
+#[derive(Clone)]
+pub struct InsuranceConfig {
+    pub committee: Vec<Address>,
+    pub threshold: u32,
+    pub premium_bps: u32,
+    pub period: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Filed,
+    Paid,
+    Rejected,
+}
+
+/// An insurance claim against the pool, decided by the committee.
+#[contracttype]
+#[derive(Clone)]
+pub struct InsuranceClaim {
+    pub id: u64,
+    pub vault_id: u64,
+    pub token: Address,
+    pub amount: i128,
+    /// Hash of the off-chain incident report backing the claim.
+    pub evidence: BytesN<32>,
+    pub status: ClaimStatus,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    Locked,
+    Claimed,
+    Refunded,
+}
+
+/// Funds taken out of a vault and held under a SHA-256 hashlock.
+///
+/// The beneficiary receives them by revealing the preimage before
+/// `expires_at`; after that they can only be refunded to the vault.
+#[contracttype]
+#[derive(Clone)]
+pub struct Escrow {
+    pub id: u64,
+    pub vault_id: u64,
+    pub token: Address,
+    pub amount: i128,
+    pub beneficiary: Address,
+    pub hashlock: BytesN<32>,
+    pub expires_at: u64,
+    pub status: EscrowStatus,
+}
+
+/// A token grant released linearly to `beneficiary` between `start` and
+/// `end`, with nothing claimable before `cliff`.
+///
+/// The granted amount leaves the vault's balance when the schedule is
+/// created; revocation returns whatever has not vested yet.
+#[contracttype]
+#[derive(Clone)]
+pub struct VestingSchedule {
+    pub id: u64,
+    pub vault_id: u64,
+    pub beneficiary: Address,
+    pub token: Address,
+    pub total: i128,
+    pub claimed: i128,
+    pub cliff: u64,
+    pub start: u64,
+    pub end: u64,
+    pub revoked: bool,
+}
+
+/// Withdrawals of at least `min_amount` need `threshold` signer weight and
+/// wait `delay` seconds once approved.
+///
+/// Amounts below the first tier use the account's `required_signatures` and
+/// `withdrawal_delay`; a tier never lowers either of them. `min_amount` is in
+/// the account's policy unit (see `set_price_oracle`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalTier {
+    pub min_amount: i128,
+    pub threshold: u32,
+    pub delay: u64,
+}
+
+/// At most `cap` may leave the account within any rolling `period` seconds.
+///
+/// Amounts are summed across all tokens held by the account, in its policy
+/// unit (see `set_price_oracle`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VelocityLimit {
+    pub period: u64,
+    pub cap: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpendRecord {
+    pub timestamp: u64,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProposalStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+/// A withdrawal waiting for signer approvals, kept entirely on-chain.
+#[contracttype]
+#[derive(Clone)]
+pub struct WithdrawalProposal {
+    pub id: u64,
+    pub vault_id: u64,
+    pub proposer: Address,
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub approvals: Vec<Address>,
+    /// Ledger timestamp at which the approval threshold was reached; cleared
+    /// if approvals drop below it. The proposal may execute once the delay
+    /// for its amount, priced at execution, has passed since then.
+    pub queued_at: Option<u64>,
+    pub status: ProposalStatus,
+}
+
+/// Asset identifier of the SEP-40 price feed interface.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// A SEP-40 price quote, scaled by the feed's decimals.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// How long storage entries are kept alive, in ledgers.
+///
+/// Every write extends its entry to `*_extend_to` once the remaining TTL has
+/// dropped below `*_threshold`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub instance_threshold: u32,
+    pub instance_extend_to: u32,
+    pub persistent_threshold: u32,
+    pub persistent_extend_to: u32,
+}
+
+/// Contract administrators and how many of them must co-sign admin actions.
+#[contracttype]
+#[derive(Clone)]
+pub struct AdminConfig {
+    pub admins: Vec<Address>,
+    pub threshold: u32,
+}
+
 // Key mapping type for storage
 #[contracttype]
+#[derive(Clone)]
 pub enum CustodyBook {
-    Account(Address),
+    Account(u64),
+    Proposal(u64),
+    InsurancePool(Address),
+    Claim(u64),
+    Recovery(u64),
+    Inheritance(u64),
+    Escrow(u64),
+    Vesting(u64),
+    /// IDs of the vaults an address owns.
+    OwnerVaults(Address),
 }
 
 // Global key for total custody accounts counter
 const TOTAL_ACCOUNTS: Symbol = symbol_short!("TOT_ACC");
 
+// Global key for the next custody vault ID
+const NEXT_VAULT_ID: Symbol = symbol_short!("NXT_VLT");
+
+// Global key for the next escrow ID
+const NEXT_ESCROW_ID: Symbol = symbol_short!("NXT_ESC");
+
+// Global key for the next vesting schedule ID
+const NEXT_VESTING_ID: Symbol = symbol_short!("NXT_VST");
+
+// Global key for the next withdrawal proposal ID
+const NEXT_PROPOSAL_ID: Symbol = symbol_short!("NXT_PROP");
+
+// Global key for the next insurance claim ID
+const NEXT_CLAIM_ID: Symbol = symbol_short!("NXT_CLM");
+
+// Global key for the insurance parameters
+const INSURANCE_CONFIG: Symbol = symbol_short!("INS_CFG");
+
+// Premiums are expressed in basis points of the coverage cap
+const BPS_DENOMINATOR: i128 = 10_000;
+
+// Global key for the admin set
+const ADMIN_CONFIG: Symbol = symbol_short!("ADMIN");
+
+// Global key for the storage lifetimes, when changed from the defaults
+const TTL_CONFIG: Symbol = symbol_short!("TTL_CFG");
+
+// Global key holding the reason code while the contract is paused
+const PAUSED: Symbol = symbol_short!("PAUSED");
+
+// Global key for the storage schema version
+const SCHEMA_VERSION: Symbol = symbol_short!("SCHEMA");
+
+// Storage layout written by this build; bump it together with `migrate`
+const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+// Default storage lifetimes, in ledgers (~5s each); see `set_ttl_config`
+const DAY_IN_LEDGERS: u32 = 17_280;
+const INSTANCE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+const PERSISTENT_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const PERSISTENT_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+
+// ----------------------------------------------------------
+// 🚨 Errors
+// ----------------------------------------------------------
+
+/// Failure codes returned by the core custody entry points.
+///
+/// Codes are part of the public interface: never renumber an existing
+/// variant. The contract spec caps an error enum at 50 cases, so this one is
+/// full; newer subsystems define their own enum continuing the code sequence.
+/// Entry points that can fail with more than one enum return
+/// `soroban_sdk::Error`, which each of them converts into.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum CustodyError {
+    AccountNotFound = 1,
+    AccountAlreadyExists = 2,
+    AccountInactive = 3,
+    InvalidAmount = 4,
+    InsufficientBalance = 5,
+    ThresholdTooLow = 6,
+    NotEnoughSigners = 7,
+    DuplicateSigner = 8,
+    NotASigner = 9,
+    InsufficientSignatures = 10,
+    NotAuthorizedToPropose = 11,
+    ProposalNotFound = 12,
+    ProposalNotPending = 13,
+    AlreadyApproved = 14,
+    NotApproved = 15,
+    InsufficientApprovals = 16,
+    InvalidTtl = 17,
+    WithdrawalNotQueued = 18,
+    WithdrawalLocked = 19,
+    WithdrawalDelayActive = 20,
+    NotAuthorizedToCancel = 21,
+    InvalidVelocityLimit = 22,
+    VelocityLimitExceeded = 23,
+    DestinationNotAllowed = 24,
+    DestinationNotActive = 25,
+    NotInitialized = 26,
+    /// Reserved: the admin set is fixed by the constructor, so this is no
+    /// longer returned. Kept so later codes do not shift.
+    AlreadyInitialized = 27,
+    NotAnAdmin = 28,
+    UnsupportedSchemaVersion = 29,
+    ContractPaused = 30,
+    ContractNotPaused = 31,
+    InsuranceNotConfigured = 32,
+    InvalidInsuranceConfig = 33,
+    NotInsured = 34,
+    NotOnCommittee = 35,
+    CoverageInactive = 36,
+    CoverageExceeded = 37,
+    PremiumNotDue = 38,
+    InsufficientPool = 39,
+    ClaimNotFound = 40,
+    ClaimNotPending = 41,
+    InvalidGuardianConfig = 42,
+    NotAGuardian = 43,
+    RecoveryNotFound = 44,
+    RecoveryInProgress = 45,
+    RecoveryLocked = 46,
+    InvalidSignerWeight = 47,
+    MissingRole = 48,
+    InvalidApprovalTier = 49,
+    SameVault = 50,
+}
+
+/// Failure codes of the escrow entry points.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum EscrowError {
+    InvalidTimeout = 51,
+    EscrowNotFound = 52,
+    EscrowNotLocked = 53,
+    EscrowExpired = 54,
+    EscrowNotExpired = 55,
+    InvalidPreimage = 56,
+}
+
+/// Failure codes of the vesting entry points.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum VestingError {
+    InvalidSchedule = 57,
+    VestingNotFound = 58,
+    NothingToClaim = 59,
+    VestingRevoked = 60,
+}
+
+/// Failure codes of the inheritance entry points.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum InheritanceError {
+    InvalidInheritanceConfig = 61,
+    AccountNotDormant = 62,
+    InheritanceInProgress = 63,
+    InheritanceNotFound = 64,
+    InheritanceLocked = 65,
+}
+
+/// Failure codes of price oracle lookups.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OracleError {
+    InvalidOracleConfig = 66,
+    PriceUnavailable = 67,
+    PriceStale = 68,
+    PriceOverflow = 69,
+}
+
 // ----------------------------------------------------------
 // ⚙️ Contract Definition
 // ----------------------------------------------------------
@@ -35,156 +475,3262 @@ pub struct AssetCustodyContract;
 
 #[contractimpl]
 impl AssetCustodyContract {
-    /// Create a new custody account.
+    /// Set the contract admin set at deployment.
+    ///
+    /// Runs as part of the deploy transaction, so nobody can claim the admin
+    /// set between deployment and setup.
+    ///
+    /// Requirements:
+    /// - `admins` must be unique; `threshold` must be between 1 and the
+    ///   number of admins.
+    pub fn __constructor(
+        env: Env,
+        admins: Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), CustodyError> {
+        if threshold == 0 {
+            return Err(CustodyError::ThresholdTooLow);
+        }
+
+        if has_duplicates(&admins) {
+            return Err(CustodyError::DuplicateSigner);
+        }
+
+        if admins.len() < threshold {
+            return Err(CustodyError::NotEnoughSigners);
+        }
+
+        let config = AdminConfig { admins, threshold };
+        env.storage().instance().set(&ADMIN_CONFIG, &config);
+        env.storage()
+            .instance()
+            .set(&SCHEMA_VERSION, &CURRENT_SCHEMA_VERSION);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("init"),
+            &env.current_contract_address(),
+            (config.admins, config.threshold),
+        );
+        Ok(())
+    }
+
+    /// Replace the contract code with the already-uploaded `new_wasm_hash`.
+    ///
+    /// Call `migrate` afterwards so the new code can bring storage up to its
+    /// schema version.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    pub fn upgrade(
+        env: Env,
+        new_wasm_hash: BytesN<32>,
+        admins: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        publish_event(
+            &env,
+            symbol_short!("upgraded"),
+            &env.current_contract_address(),
+            new_wasm_hash,
+        );
+        Ok(())
+    }
+
+    /// Bring stored data up to `CURRENT_SCHEMA_VERSION` after an upgrade.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    /// - The stored schema must not be newer than this code understands.
+    pub fn migrate(env: Env, admins: Vec<Address>) -> Result<u32, CustodyError> {
+        require_admins(&env, &admins)?;
+
+        let from: u32 = env.storage().instance().get(&SCHEMA_VERSION).unwrap_or(0);
+        if from > CURRENT_SCHEMA_VERSION {
+            return Err(CustodyError::UnsupportedSchemaVersion);
+        }
+
+        // Per-version migration steps go here as the layout evolves.
+
+        env.storage()
+            .instance()
+            .set(&SCHEMA_VERSION, &CURRENT_SCHEMA_VERSION);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("migrated"),
+            &env.current_contract_address(),
+            (from, CURRENT_SCHEMA_VERSION),
+        );
+        Ok(CURRENT_SCHEMA_VERSION)
+    }
+
+    /// Change how long storage entries are kept alive on every write.
+    ///
+    /// Entries written before the change keep their TTL until they are next
+    /// written or extended.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    /// - Each `*_extend_to` must be positive, no larger than the network's
+    ///   maximum TTL, and at least its `*_threshold`.
+    pub fn set_ttl_config(
+        env: Env,
+        config: TtlConfig,
+        admins: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        let max_ttl = env.storage().max_ttl();
+        for (threshold, extend_to) in [
+            (config.instance_threshold, config.instance_extend_to),
+            (config.persistent_threshold, config.persistent_extend_to),
+        ] {
+            if extend_to == 0 || extend_to > max_ttl || threshold > extend_to {
+                return Err(CustodyError::InvalidTtl);
+            }
+        }
+
+        env.storage().instance().set(&TTL_CONFIG, &config);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("ttl_cfg"),
+            &env.current_contract_address(),
+            config,
+        );
+        Ok(())
+    }
+
+    /// Emergency stop: block every outflow from every custody account.
+    ///
+    /// Deposits and policy changes keep working. `reason` is an
+    /// operator-defined incident code reported in the event and by `paused`.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    pub fn pause(env: Env, reason: u32, admins: Vec<Address>) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        env.storage().instance().set(&PAUSED, &reason);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("paused"),
+            &env.current_contract_address(),
+            reason,
+        );
+
+        log!(&env, "⛔ Contract paused (reason {})", reason);
+        Ok(())
+    }
+
+    /// Lift an emergency pause.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    /// - The contract must be paused.
+    pub fn unpause(env: Env, admins: Vec<Address>) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        if !env.storage().instance().has(&PAUSED) {
+            return Err(CustodyError::ContractNotPaused);
+        }
+
+        env.storage().instance().remove(&PAUSED);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("unpaused"),
+            &env.current_contract_address(),
+            (),
+        );
+        Ok(())
+    }
+
+    /// Freeze a single custody account by clearing `is_active`.
+    ///
+    /// A frozen account rejects deposits, withdrawals and policy changes
+    /// until it is unfrozen. `reason` is reported in the event.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    pub fn freeze_account(
+        env: Env,
+        vault_id: u64,
+        reason: u32,
+        admins: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_account(&env, &account_key)?;
+
+        account.is_active = false;
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("frozen"), &account, reason);
+        Ok(())
+    }
+
+    /// Reactivate a frozen custody account.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    pub fn unfreeze_account(
+        env: Env,
+        vault_id: u64,
+        admins: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_account(&env, &account_key)?;
+
+        account.is_active = true;
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("unfrozen"), &account, ());
+        Ok(())
+    }
+
+    /// Create a new custody vault owned by `owner` and return its ID.
+    ///
+    /// An owner may hold any number of vaults, e.g. separate operating,
+    /// reserve and payroll vaults; see `list_vaults`.
     ///
     /// Requirements:
     /// - The caller must authorize the call (`require_auth()`).
     /// - Minimum `required_signatures` is 2 for multi-sig safety.
+    /// - Every signer weight must be positive and below `required_signatures`,
+    ///   so no single key can approve alone, and together they must reach it.
+    /// - `withdrawal_delay` is in seconds; 0 allows immediate withdrawals.
+    ///
+    /// Every signer starts with the `Proposer` and `Approver` roles; use
+    /// `set_roles` to separate duties.
     pub fn create_custody_account(
         env: Env,
         owner: Address,
+        signers: Map<Address, u32>,
         required_signatures: u32,
+        withdrawal_delay: u64,
         insurance: bool,
-    ) -> bool {
+    ) -> Result<u64, CustodyError> {
         owner.require_auth();
 
-        let account_key = CustodyBook::Account(owner.clone());
+        if required_signatures < 2 {
+            return Err(CustodyError::ThresholdTooLow);
+        }
+
+        if signers
+            .values()
+            .iter()
+            .any(|weight| weight == 0 || weight >= required_signatures)
+        {
+            return Err(CustodyError::InvalidSignerWeight);
+        }
 
-        // Reject duplicates
-        if env.storage().instance().has(&account_key) {
-            log!(&env, "⚠️ Custody account already exists");
-            return false;
+        if total_weight(&signers) < required_signatures {
+            return Err(CustodyError::NotEnoughSigners);
         }
 
-        if required_signatures < 2 {
-            panic!("❌ Multi-sig requires at least 2 signatures");
+        let id: u64 = env.storage().instance().get(&NEXT_VAULT_ID).unwrap_or(0);
+        env.storage().instance().set(&NEXT_VAULT_ID, &(id + 1));
+
+        let mut roles = Map::new(&env);
+        for signer in signers.keys().iter() {
+            roles.set(signer, vec![&env, Role::Proposer, Role::Approver]);
         }
 
         let account = CustodyAccount {
+            id,
             owner: owner.clone(),
-            balance: 0,
+            balances: Map::new(&env),
+            signers,
             required_signatures,
+            withdrawal_delay,
+            pending_withdrawal_delay: None,
+            withdrawal_delay_effective_at: 0,
+            approval_tiers: Vec::new(&env),
+            velocity_limits: Vec::new(&env),
+            price_oracle: None,
+            max_price_age: 0,
+            spend_log: Vec::new(&env),
+            allowlist: Map::new(&env),
+            allowlist_delay: 0,
+            pending_allowlist_delay: None,
+            allowlist_delay_effective_at: 0,
             is_insured: insurance,
+            coverage: Map::new(&env),
+            roles,
+            guardians: Vec::new(&env),
+            guardian_threshold: 0,
+            recovery_period: 0,
+            last_activity: env.ledger().timestamp(),
+            heir: None,
+            dormancy_period: 0,
+            inheritance_grace: 0,
             is_active: true,
         };
 
-        env.storage().instance().set(&account_key, &account);
+        save_account(&env, &CustodyBook::Account(id), &account);
+
+        let mut vault_ids = load_vault_ids(&env, &owner);
+        vault_ids.push_back(id);
+        save_vault_ids(&env, &owner, &vault_ids);
 
         // Update total account counter
         let mut total: u64 = env.storage().instance().get(&TOTAL_ACCOUNTS).unwrap_or(0);
         total += 1;
         env.storage().instance().set(&TOTAL_ACCOUNTS, &total);
 
-        // Extend storage life
-        env.storage().instance().extend_ttl(5000, 5000);
+        publish_vault_event(
+            &env,
+            symbol_short!("created"),
+            &account,
+            (account.required_signatures, account.is_insured),
+        );
 
-        log!(&env, "✅ Custody account created for {}", owner);
-        true
+        log!(&env, "✅ Custody vault {} created for {}", id, owner);
+        Ok(id)
     }
 
-    /// Deposit assets into a custody account.
+    /// Deposit `token` into a custody account.
+    ///
+    /// The tokens are pulled from the vault owner into the contract, so the
+    /// recorded balance is always backed by assets held on-chain.
     ///
     /// Requirements:
     /// - Caller must be the account owner.
     /// - Amount must be positive.
-    pub fn deposit_assets(env: Env, owner: Address, amount: i128) -> bool {
-        owner.require_auth();
-
+    /// - `token` must be a Soroban token contract (e.g. a Stellar Asset Contract).
+    pub fn deposit_assets(
+        env: Env,
+        vault_id: u64,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), CustodyError> {
         if amount <= 0 {
-            panic!("❌ Deposit amount must be positive");
+            return Err(CustodyError::InvalidAmount);
         }
 
-        let account_key = CustodyBook::Account(owner.clone());
-        let mut account: CustodyAccount = env
-            .storage()
-            .instance()
-            .get(&account_key)
-            .unwrap_or_else(|| panic!("⚠️ Custody account not found"));
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        account.owner.require_auth();
+        account.last_activity = env.ledger().timestamp();
+
+        let owner = account.owner.clone();
+        let balance = take_deposit(&env, &account_key, &mut account, &owner, &token, amount);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("deposit"),
+            &account,
+            (token, amount, balance),
+        );
+
+        log!(&env, "💰 Deposit successful. New balance: {}", balance);
+        Ok(())
+    }
+
+    /// Deposit `token` from `from` into someone else's custody vault, e.g. a
+    /// customer or counterparty paying in.
+    ///
+    /// Requirements:
+    /// - `from` must authorize the call; the vault owner does not.
+    /// - Amount must be positive.
+    /// - `token` must be a Soroban token contract (e.g. a Stellar Asset Contract).
+    pub fn deposit_for(
+        env: Env,
+        from: Address,
+        vault_id: u64,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), CustodyError> {
+        from.require_auth();
 
-        if !account.is_active {
-            panic!("⚠️ Custody account is not active");
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount);
         }
 
-        account.balance += amount;
-        env.storage().instance().set(&account_key, &account);
-        env.storage().instance().extend_ttl(5000, 5000);
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
 
-        log!(
+        let balance = take_deposit(&env, &account_key, &mut account, &from, &token, amount);
+
+        publish_vault_event(
             &env,
-            "💰 Deposit successful. New balance: {} XLM",
-            account.balance
+            symbol_short!("dep_for"),
+            &account,
+            (from, token, amount, balance),
         );
-        true
+
+        log!(&env, "💰 Deposit for vault {} successful", vault_id);
+        Ok(())
     }
 
-    /// Withdraw assets with multi-signature verification.
+    /// Withdraw `token` with multi-signature verification.
+    ///
+    /// The tokens are sent from the contract to `to`, which must be an active
+    /// entry of the account's destination allowlist.
     ///
     /// Requirements:
-    /// - Caller must be the account owner.
-    /// - Signatures count must meet required threshold.
+    /// - `caller` must hold the `Proposer` role and authorize the call.
+    /// - Every address in `signers` must be a registered signer of the account
+    ///   with the `Approver` role and must authorize this invocation.
+    /// - The combined weight of `signers` must meet the threshold for
+    ///   `amount` (see `set_approval_tiers`).
+    /// - No withdrawal delay may apply to `amount`; otherwise use
+    ///   `queue_withdrawal`.
     /// - Sufficient balance must exist.
     pub fn withdraw_assets(
         env: Env,
-        owner: Address,
+        vault_id: u64,
+        caller: Address,
+        token: Address,
+        to: Address,
         amount: i128,
-        signatures_count: u32,
-    ) -> bool {
-        owner.require_auth();
-
+        signers: Vec<Address>,
+    ) -> Result<(), Error> {
         if amount <= 0 {
-            panic!("❌ Withdrawal amount must be positive");
+            return Err(CustodyError::InvalidAmount.into());
         }
 
-        let account_key = CustodyBook::Account(owner.clone());
-        let mut account: CustodyAccount = env
-            .storage()
-            .instance()
-            .get(&account_key)
-            .unwrap_or_else(|| panic!("⚠️ Custody account not found"));
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
 
-        if !account.is_active {
-            panic!("⚠️ Custody account inactive");
-        }
+        let value = policy_value(&env, &account, &token, amount)?;
+        let (threshold, delay) = approval_rule(&account, value);
+        require_caller_and_signers(&account, &caller, Role::Proposer, &signers, threshold)?;
 
-        if signatures_count < account.required_signatures {
-            panic!(
-                "🚫 Insufficient signatures. Required: {}, Provided: {}",
-                account.required_signatures, signatures_count
-            );
+        if delay > 0 {
+            return Err(CustodyError::WithdrawalDelayActive.into());
         }
 
-        if account.balance < amount {
-            panic!("🚫 Insufficient balance for withdrawal");
+        pay_out(&env, &account_key, &mut account, &token, &to, amount, value)?;
+        Ok(())
+    }
+
+    /// Queue a fully signed withdrawal behind the withdrawal delay for its
+    /// amount.
+    ///
+    /// The withdrawal is stored as an already-approved proposal that unlocks
+    /// once the delay has passed; run it with `execute_withdrawal` or stop it
+    /// with `cancel_withdrawal`. Returns the proposal ID.
+    ///
+    /// Requirements:
+    /// - Same authorization as `withdraw_assets`.
+    pub fn queue_withdrawal(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        token: Address,
+        to: Address,
+        amount: i128,
+        signers: Vec<Address>,
+    ) -> Result<u64, Error> {
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount.into());
         }
 
-        account.balance -= amount;
-        env.storage().instance().set(&account_key, &account);
-        env.storage().instance().extend_ttl(5000, 5000);
+        let account = load_active_account(&env, &CustodyBook::Account(vault_id))?;
+
+        let value = policy_value(&env, &account, &token, amount)?;
+        let (threshold, delay) = approval_rule(&account, value);
+        require_caller_and_signers(&account, &caller, Role::Proposer, &signers, threshold)?;
+
+        let now = env.ledger().timestamp();
+        let unlock_at = now
+            .checked_add(delay)
+            .ok_or(CustodyError::WithdrawalDelayActive)?;
+        let proposal = WithdrawalProposal {
+            id: next_proposal_id(&env),
+            vault_id,
+            proposer: caller,
+            token,
+            to,
+            amount,
+            approvals: signers,
+            queued_at: Some(now),
+            status: ProposalStatus::Pending,
+        };
+
+        save_proposal(&env, &CustodyBook::Proposal(proposal.id), &proposal);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("queued"),
+            &account,
+            (proposal.id, unlock_at),
+        );
 
         log!(
             &env,
-            "✅ Withdrawal of {} XLM successful. Remaining: {}",
-            amount,
-            account.balance
+            "⏳ Withdrawal {} queued until {}",
+            proposal.id,
+            unlock_at
         );
-        true
+        Ok(proposal.id)
     }
 
-    /// View custody account details.
-    pub fn view_custody_account(env: Env, owner: Address) -> CustodyAccount {
-        let account_key = CustodyBook::Account(owner.clone());
+    /// Move `amount` of `token` between two vaults of the same owner without
+    /// the tokens leaving the contract.
+    ///
+    /// The source vault's approval threshold and velocity limits apply. Funds
+    /// for anyone else must be withdrawn to an allowlisted destination.
+    ///
+    /// Requirements:
+    /// - Same authorization as `withdraw_assets`, on the source vault.
+    /// - No withdrawal delay may apply to `amount`.
+    /// - Both vaults must be active, distinct and have the same owner.
+    /// - The contract must not be paused.
+    pub fn transfer_between_accounts(
+        env: Env,
+        from_vault: u64,
+        caller: Address,
+        to_vault: u64,
+        token: Address,
+        amount: i128,
+        signers: Vec<Address>,
+    ) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount.into());
+        }
+
+        if from_vault == to_vault {
+            return Err(CustodyError::SameVault.into());
+        }
 
-        env.storage().instance().get(&account_key).unwrap_or(CustodyAccount {
-            owner: owner.clone(),
-            balance: 0,
-            required_signatures: 0,
-            is_insured: false,
-            is_active: false,
-        })
-    }
+        let from_key = CustodyBook::Account(from_vault);
+        let mut from = load_active_account(&env, &from_key)?;
+        let to_key = CustodyBook::Account(to_vault);
+        let mut to = load_active_account(&env, &to_key)?;
 
-    /// Get total number of custody accounts created (global metric).
-    pub fn total_accounts(env: Env) -> u64 {
-        env.storage().instance().get(&TOTAL_ACCOUNTS).unwrap_or(0)
-    }
-}
+        if to.owner != from.owner {
+            return Err(CustodyError::DestinationNotAllowed.into());
+        }
+
+        let value = policy_value(&env, &from, &token, amount)?;
+        let (threshold, delay) = approval_rule(&from, value);
+        require_caller_and_signers(&from, &caller, Role::Proposer, &signers, threshold)?;
+
+        if delay > 0 {
+            return Err(CustodyError::WithdrawalDelayActive.into());
+        }
+
+        if env.storage().instance().has(&PAUSED) {
+            return Err(CustodyError::ContractPaused.into());
+        }
+
+        let from_balance = debit(&env, &mut from, &token, amount, value)?;
+        save_account(&env, &from_key, &from);
+
+        let to_balance = balance_of(&to, &token) + amount;
+        to.balances.set(token.clone(), to_balance);
+        save_account(&env, &to_key, &to);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("transfer"),
+            &from,
+            (to_vault, token, amount, from_balance, to_balance),
+        );
+
+        log!(
+            &env,
+            "🔁 Moved {} from vault {} to vault {}",
+            amount,
+            from_vault,
+            to_vault
+        );
+        Ok(())
+    }
+
+    /// Change the account's withdrawal delay (in seconds).
+    ///
+    /// An increase applies at once. A reduction only applies once the current
+    /// delay has passed, so the signers it is meant to slow down cannot skip
+    /// it by lowering it first.
+    ///
+    /// Requirements:
+    /// - `caller` must hold the `Admin` role and authorize the call.
+    /// - `signers` must meet the threshold as for `withdraw_assets`.
+    pub fn set_withdrawal_delay(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        withdrawal_delay: u64,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        let now = env.ledger().timestamp();
+        let effective_at = if withdrawal_delay >= account.withdrawal_delay {
+            account.withdrawal_delay = withdrawal_delay;
+            account.pending_withdrawal_delay = None;
+            now
+        } else {
+            account.pending_withdrawal_delay = Some(withdrawal_delay);
+            account.withdrawal_delay_effective_at = now.saturating_add(account.withdrawal_delay);
+            account.withdrawal_delay_effective_at
+        };
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("delay"),
+            &account,
+            (withdrawal_delay, effective_at),
+        );
+        Ok(())
+    }
+
+    /// Add `signer` with the given `weight` to the account's signer set and
+    /// grant it the `Approver` role.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`, but with the highest
+    ///   threshold any withdrawal can require, since a new signer adds
+    ///   approval weight.
+    /// - `signer` must not already be a signer; `weight` must be positive and
+    ///   below `required_signatures`.
+    pub fn add_signer(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        signer: Address,
+        weight: u32,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            highest_threshold(&account),
+        )?;
+
+        if account.signers.contains_key(signer.clone()) {
+            return Err(CustodyError::DuplicateSigner);
+        }
+
+        if weight == 0 || weight >= account.required_signatures {
+            return Err(CustodyError::InvalidSignerWeight);
+        }
+
+        account.signers.set(signer.clone(), weight);
+        grant_role(&env, &mut account, &signer, Role::Approver);
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("sgn_add"), &account, (signer, weight));
+        Ok(())
+    }
+
+    /// Remove `signer` from the account's signer set, together with every
+    /// role it held in the account.
+    ///
+    /// Approvals the removed signer gave on pending proposals stop counting.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - `signer` must be a signer, and the remaining signers must still
+    ///   reach `required_signatures` and every approval tier threshold.
+    pub fn remove_signer(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        signer: Address,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        if account.signers.remove(signer.clone()).is_none() {
+            return Err(CustodyError::NotASigner);
+        }
+
+        if total_weight(&account.signers) < highest_threshold(&account) {
+            return Err(CustodyError::NotEnoughSigners);
+        }
+
+        account.roles.remove(signer.clone());
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("sgn_rm"), &account, signer);
+        Ok(())
+    }
+
+    /// Change the signer weight needed to approve a withdrawal.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`, checked against the
+    ///   current threshold. Lowering the threshold needs the highest
+    ///   threshold any withdrawal can require.
+    /// - `required_signatures` must be at least 2, above every single signer
+    ///   weight, and at most the total signer weight.
+    pub fn set_threshold(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        required_signatures: u32,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let threshold = if required_signatures >= account.required_signatures {
+            account.required_signatures
+        } else {
+            highest_threshold(&account)
+        };
+        require_caller_and_signers(&account, &caller, Role::Admin, &signers, threshold)?;
+
+        if required_signatures < 2
+            || account
+                .signers
+                .values()
+                .iter()
+                .any(|weight| weight >= required_signatures)
+        {
+            return Err(CustodyError::ThresholdTooLow);
+        }
+
+        if total_weight(&account.signers) < required_signatures {
+            return Err(CustodyError::NotEnoughSigners);
+        }
+
+        account.required_signatures = required_signatures;
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("threshold"),
+            &account,
+            required_signatures,
+        );
+        Ok(())
+    }
+
+    /// Replace the account's approval tiers, e.g. `(1_000, 3, 0)` and
+    /// `(100_000, 4, 86_400)` on top of a base threshold of 2.
+    ///
+    /// An empty list applies the base threshold and delay to every amount.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`. Lowering the threshold
+    ///   or delay of any amount needs the highest threshold any withdrawal can
+    ///   require, so the base quorum cannot lift a tier and then withdraw.
+    /// - `min_amount` must be positive and strictly increasing.
+    /// - Every `threshold` must be reachable by the current signer weights.
+    pub fn set_approval_tiers(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        tiers: Vec<ApprovalTier>,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let threshold = if tightens_tiers(&account, &tiers) {
+            account.required_signatures
+        } else {
+            highest_threshold(&account)
+        };
+        require_caller_and_signers(&account, &caller, Role::Admin, &signers, threshold)?;
+
+        let available = total_weight(&account.signers);
+        let mut previous_min = 0;
+        for tier in tiers.iter() {
+            if tier.min_amount <= previous_min || tier.threshold > available {
+                return Err(CustodyError::InvalidApprovalTier);
+            }
+            previous_min = tier.min_amount;
+        }
+
+        account.approval_tiers = tiers.clone();
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("tiers"), &account, tiers);
+        Ok(())
+    }
+
+    /// Replace the roles of `member`; an empty list revokes all of them.
+    ///
+    /// The owner holds every role regardless of this setting.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`. Granting a role the
+    ///   member does not hold yet needs the highest threshold any withdrawal
+    ///   can require.
+    pub fn set_roles(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        member: Address,
+        roles: Vec<Role>,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let threshold = if roles.iter().all(|role| has_role(&account, &member, role)) {
+            account.required_signatures
+        } else {
+            highest_threshold(&account)
+        };
+        require_caller_and_signers(&account, &caller, Role::Admin, &signers, threshold)?;
+
+        if roles.is_empty() {
+            account.roles.remove(member.clone());
+        } else {
+            account.roles.set(member.clone(), roles.clone());
+        }
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("roles"), &account, (member, roles));
+        Ok(())
+    }
+
+    /// Replace the account's rolling withdrawal caps, e.g. a daily
+    /// `(86_400, cap)` and a weekly `(604_800, cap)` limit.
+    ///
+    /// An empty list removes all caps.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`. Raising or removing a
+    ///   cap needs the highest threshold any withdrawal can require, so the
+    ///   signers a cap holds back cannot lift it themselves.
+    /// - Every limit needs a positive `period` and a non-negative `cap`.
+    pub fn set_velocity_limits(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        limits: Vec<VelocityLimit>,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let threshold = if tightens_limits(&account.velocity_limits, &limits) {
+            account.required_signatures
+        } else {
+            highest_threshold(&account)
+        };
+        require_caller_and_signers(&account, &caller, Role::Admin, &signers, threshold)?;
+
+        for limit in limits.iter() {
+            if limit.period == 0 || limit.cap < 0 {
+                return Err(CustodyError::InvalidVelocityLimit);
+            }
+        }
+
+        account.velocity_limits = limits.clone();
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("velocity"), &account, limits);
+        Ok(())
+    }
+
+    /// Price the account's approval tiers and velocity limits in USD through
+    /// a SEP-40 `oracle`, or in raw token units when `oracle` is `None`.
+    ///
+    /// A USD amount is `amount * price / 10^token_decimals`, i.e. it carries
+    /// the oracle's decimals. Update tiers and limits together with the
+    /// oracle, since their unit changes with it.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`, but with the highest
+    ///   threshold any withdrawal can require, since a new price can move
+    ///   amounts out of their tiers.
+    /// - With an oracle, `max_price_age` must be positive; older prices are
+    ///   rejected and block the withdrawal.
+    pub fn set_price_oracle(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        oracle: Option<Address>,
+        max_price_age: u64,
+        signers: Vec<Address>,
+    ) -> Result<(), Error> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            highest_threshold(&account),
+        )?;
+
+        if oracle.is_some() && max_price_age == 0 {
+            return Err(OracleError::InvalidOracleConfig.into());
+        }
+
+        account.price_oracle = oracle.clone();
+        account.max_price_age = max_price_age;
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("oracle"),
+            &account,
+            (oracle, max_price_age),
+        );
+        Ok(())
+    }
+
+    /// Allow withdrawals to `destination` once the account's
+    /// `allowlist_delay` has passed.
+    ///
+    /// Re-adding an existing destination restarts its activation delay.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    pub fn add_allowed_destination(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        destination: Address,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        settle_allowlist_delay(&env, &mut account);
+        let active_from = env
+            .ledger()
+            .timestamp()
+            .saturating_add(account.allowlist_delay);
+        account.allowlist.set(destination.clone(), active_from);
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("allow"),
+            &account,
+            (destination, active_from),
+        );
+        Ok(())
+    }
+
+    /// Remove `destination` from the allowlist with immediate effect.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - `destination` must be on the allowlist.
+    pub fn remove_allowed_destination(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        destination: Address,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        if account.allowlist.remove(destination.clone()).is_none() {
+            return Err(CustodyError::DestinationNotAllowed);
+        }
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("disallow"), &account, destination);
+        Ok(())
+    }
+
+    /// Change the activation delay (in seconds) for new allowlist entries.
+    ///
+    /// An increase applies at once. A reduction only applies once the current
+    /// delay has passed, so it cannot be used to activate a destination
+    /// sooner than the delay in force when the change was made; entries
+    /// added in the meantime still wait the current delay.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    pub fn set_allowlist_delay(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        allowlist_delay: u64,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        settle_allowlist_delay(&env, &mut account);
+        let now = env.ledger().timestamp();
+        let effective_at = if allowlist_delay >= account.allowlist_delay {
+            account.allowlist_delay = allowlist_delay;
+            account.pending_allowlist_delay = None;
+            now
+        } else {
+            account.pending_allowlist_delay = Some(allowlist_delay);
+            account.allowlist_delay_effective_at = now.saturating_add(account.allowlist_delay);
+            account.allowlist_delay_effective_at
+        };
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("allow_dly"),
+            &account,
+            (allowlist_delay, effective_at),
+        );
+        Ok(())
+    }
+
+    /// Replace the account's guardian set used for social recovery.
+    ///
+    /// An empty `guardians` list with a zero `threshold` disables recovery.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - No recovery may be in progress.
+    /// - `guardians` must be unique and not include the owner; `threshold`
+    ///   must be between 1 and the number of guardians; `recovery_period`
+    ///   must be positive so the owner has time to cancel a recovery.
+    pub fn set_guardians(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        guardians: Vec<Address>,
+        threshold: u32,
+        recovery_period: u64,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        if env
+            .storage()
+            .persistent()
+            .has(&CustodyBook::Recovery(vault_id))
+        {
+            return Err(CustodyError::RecoveryInProgress);
+        }
+
+        let disabled = guardians.is_empty() && threshold == 0;
+        if !disabled
+            && (threshold == 0
+                || recovery_period == 0
+                || guardians.len() < threshold
+                || has_duplicates(&guardians)
+                || guardians.contains(&account.owner))
+        {
+            return Err(CustodyError::InvalidGuardianConfig);
+        }
+
+        account.guardians = guardians.clone();
+        account.guardian_threshold = threshold;
+        account.recovery_period = recovery_period;
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("guardians"),
+            &account,
+            (guardians, threshold, recovery_period),
+        );
+        Ok(())
+    }
+
+    /// Start moving the account to `new_owner`, e.g. after the owner key
+    /// was lost.
+    ///
+    /// The move can be finalized after the account's `recovery_period`;
+    /// until then the current owner can cancel it.
+    ///
+    /// Requirements:
+    /// - `guardians` must be a quorum of the account's guardians and
+    ///   authorize the call.
+    /// - No other recovery may be in progress.
+    pub fn start_recovery(
+        env: Env,
+        vault_id: u64,
+        new_owner: Address,
+        guardians: Vec<Address>,
+    ) -> Result<u64, CustodyError> {
+        let account = load_active_account(&env, &CustodyBook::Account(vault_id))?;
+
+        if account.guardian_threshold == 0 {
+            return Err(CustodyError::InvalidGuardianConfig);
+        }
+
+        require_quorum(
+            &account.guardians,
+            account.guardian_threshold,
+            &guardians,
+            CustodyError::NotAGuardian,
+        )?;
+
+        let recovery_key = CustodyBook::Recovery(vault_id);
+        if env.storage().persistent().has(&recovery_key) {
+            return Err(CustodyError::RecoveryInProgress);
+        }
+
+        let executable_at = env
+            .ledger()
+            .timestamp()
+            .checked_add(account.recovery_period)
+            .ok_or(CustodyError::InvalidGuardianConfig)?;
+        let recovery = PendingRecovery {
+            new_owner: new_owner.clone(),
+            guardians,
+            executable_at,
+        };
+        save_recovery(&env, &recovery_key, &recovery);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("recovery"),
+            &account,
+            (new_owner, executable_at),
+        );
+        Ok(executable_at)
+    }
+
+    /// Cancel a recovery during its challenge period.
+    ///
+    /// Requirements:
+    /// - Caller must be the current account owner.
+    pub fn cancel_recovery(env: Env, vault_id: u64) -> Result<(), CustodyError> {
+        let account = load_account(&env, &CustodyBook::Account(vault_id))?;
+        account.owner.require_auth();
+
+        let recovery_key = CustodyBook::Recovery(vault_id);
+        let recovery = load_recovery(&env, &recovery_key)?;
+        env.storage().persistent().remove(&recovery_key);
+        bump_instance(&env);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("rec_cncl"),
+            &account,
+            recovery.new_owner,
+        );
+        Ok(())
+    }
+
+    /// Hand the vault to the recovered owner once the challenge period has
+    /// passed.
+    ///
+    /// The vault keeps its ID, balances, policies and pending proposals; if
+    /// the old owner was also a signer, its weight moves to the new owner.
+    /// The old owner loses every role.
+    /// Anyone may finalize.
+    ///
+    /// Requirements:
+    /// - A recovery must be in progress and its `executable_at` reached.
+    pub fn finalize_recovery(env: Env, vault_id: u64) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let recovery_key = CustodyBook::Recovery(vault_id);
+        let recovery = load_recovery(&env, &recovery_key)?;
+
+        if env.ledger().timestamp() < recovery.executable_at {
+            return Err(CustodyError::RecoveryLocked);
+        }
+
+        env.storage().persistent().remove(&recovery_key);
+        let old_owner = hand_over(&env, &account_key, &mut account, &recovery.new_owner);
+
+        publish_vault_event(&env, symbol_short!("recovered"), &account, old_owner);
+        Ok(())
+    }
+
+    /// Name the heir who can claim the account after `dormancy_period`
+    /// seconds without activity, followed by `grace_period` seconds in which
+    /// the claim can still be defeated. `None` disables inheritance.
+    ///
+    /// Activity is an owner deposit, any outflow, a `heartbeat`, or naming
+    /// the heir, so a new heir always waits out a full dormancy period.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - No inheritance claim may be in progress; a claim defeated by
+    ///   activity is dropped.
+    /// - The heir must not be the owner, and `dormancy_period` and
+    ///   `grace_period` must be positive.
+    pub fn set_heir(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        heir: Option<Address>,
+        dormancy_period: u64,
+        grace_period: u64,
+        signers: Vec<Address>,
+    ) -> Result<(), Error> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        let inheritance_key = CustodyBook::Inheritance(vault_id);
+        if load_live_inheritance(&env, &account, &inheritance_key).is_some() {
+            return Err(InheritanceError::InheritanceInProgress.into());
+        }
+
+        if let Some(heir) = &heir {
+            if *heir == account.owner || dormancy_period == 0 || grace_period == 0 {
+                return Err(InheritanceError::InvalidInheritanceConfig.into());
+            }
+        }
+
+        env.storage().persistent().remove(&inheritance_key);
+        account.heir = heir.clone();
+        account.dormancy_period = dormancy_period;
+        account.inheritance_grace = grace_period;
+        account.last_activity = env.ledger().timestamp();
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("heir"),
+            &account,
+            (heir, dormancy_period, grace_period),
+        );
+        Ok(())
+    }
+
+    /// Record that the account is still looked after, cancelling any
+    /// inheritance claim in progress.
+    ///
+    /// Requirements:
+    /// - `caller` must hold the `Admin` role and authorize the call.
+    pub fn heartbeat(env: Env, vault_id: u64, caller: Address) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_account(&env, &account_key)?;
+        require_role(&account, &caller, Role::Admin)?;
+        caller.require_auth();
+
+        account.last_activity = env.ledger().timestamp();
+        save_account(&env, &account_key, &account);
+        env.storage()
+            .persistent()
+            .remove(&CustodyBook::Inheritance(vault_id));
+
+        publish_vault_event(
+            &env,
+            symbol_short!("heartbeat"),
+            &account,
+            (caller, account.last_activity),
+        );
+        Ok(())
+    }
+
+    /// Claim a dormant account as its heir. Returns the timestamp from which
+    /// `finalize_inheritance` can hand the account over.
+    ///
+    /// Requirements:
+    /// - Caller must be the account's heir.
+    /// - The account must have been dormant for its `dormancy_period`.
+    /// - No other claim may be in progress; a claim defeated by activity is
+    ///   replaced.
+    pub fn start_inheritance(env: Env, vault_id: u64) -> Result<u64, Error> {
+        let account = load_active_account(&env, &CustodyBook::Account(vault_id))?;
+
+        let heir = account
+            .heir
+            .clone()
+            .ok_or(InheritanceError::InvalidInheritanceConfig)?;
+        heir.require_auth();
+
+        let now = env.ledger().timestamp();
+        if now.saturating_sub(account.last_activity) < account.dormancy_period {
+            return Err(InheritanceError::AccountNotDormant.into());
+        }
+
+        let inheritance_key = CustodyBook::Inheritance(vault_id);
+        if load_live_inheritance(&env, &account, &inheritance_key).is_some() {
+            return Err(InheritanceError::InheritanceInProgress.into());
+        }
+
+        let executable_at = now
+            .checked_add(account.inheritance_grace)
+            .ok_or(InheritanceError::InvalidInheritanceConfig)?;
+        let inheritance = PendingInheritance {
+            heir: heir.clone(),
+            started_at: now,
+            executable_at,
+        };
+        save_inheritance(&env, &inheritance_key, &inheritance);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("inherit"),
+            &account,
+            (heir, executable_at),
+        );
+        Ok(executable_at)
+    }
+
+    /// Hand the account to the heir once the grace period has passed
+    /// without activity.
+    ///
+    /// Ownership moves as in `finalize_recovery`; the heir setting is
+    /// cleared. Anyone may finalize.
+    ///
+    /// Requirements:
+    /// - A claim must be in progress and its `executable_at` reached.
+    /// - The account must have seen no activity since the claim started;
+    ///   otherwise the claim is defeated and the heir has to start anew.
+    pub fn finalize_inheritance(env: Env, vault_id: u64) -> Result<(), Error> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let inheritance_key = CustodyBook::Inheritance(vault_id);
+        let inheritance = load_inheritance(&env, &inheritance_key)?;
+
+        if env.ledger().timestamp() < inheritance.executable_at {
+            return Err(InheritanceError::InheritanceLocked.into());
+        }
+
+        if account.last_activity >= inheritance.started_at {
+            return Err(InheritanceError::AccountNotDormant.into());
+        }
+
+        env.storage().persistent().remove(&inheritance_key);
+        account.heir = None;
+        account.last_activity = env.ledger().timestamp();
+        let old_owner = hand_over(&env, &account_key, &mut account, &inheritance.heir);
+
+        publish_vault_event(&env, symbol_short!("inherited"), &account, old_owner);
+        Ok(())
+    }
+
+    /// Propose a withdrawal that signers approve one by one.
+    ///
+    /// Returns the ID of the new proposal.
+    ///
+    /// Requirements:
+    /// - `proposer` must hold the `Proposer` role and authorize the call.
+    /// - Amount must be positive.
+    pub fn propose_withdrawal(
+        env: Env,
+        vault_id: u64,
+        proposer: Address,
+        token: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<u64, Error> {
+        proposer.require_auth();
+
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount.into());
+        }
+
+        let account = load_active_account(&env, &CustodyBook::Account(vault_id))?;
+
+        if !has_role(&account, &proposer, Role::Proposer) {
+            return Err(CustodyError::NotAuthorizedToPropose.into());
+        }
+
+        let id = next_proposal_id(&env);
+
+        let proposal = WithdrawalProposal {
+            id,
+            vault_id,
+            proposer,
+            token,
+            to,
+            amount,
+            approvals: Vec::new(&env),
+            queued_at: None,
+            status: ProposalStatus::Pending,
+        };
+
+        save_proposal(&env, &CustodyBook::Proposal(id), &proposal);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("proposed"),
+            &account,
+            (
+                id,
+                proposal.proposer,
+                proposal.token,
+                proposal.to,
+                proposal.amount,
+            ),
+        );
+
+        log!(&env, "📝 Withdrawal proposal {} created", id);
+        Ok(id)
+    }
+
+    /// Approve a pending withdrawal proposal.
+    ///
+    /// The approval that reaches the threshold for the proposal's amount,
+    /// priced now, queues it: it unlocks after the matching withdrawal delay,
+    /// priced again at execution.
+    ///
+    /// Requirements:
+    /// - `signer` must be a signer of the proposal's account with the
+    ///   `Approver` role and must authorize the call.
+    /// - The signer must not have approved the proposal already.
+    pub fn approve_withdrawal(env: Env, proposal_id: u64, signer: Address) -> Result<(), Error> {
+        signer.require_auth();
+
+        let proposal_key = CustodyBook::Proposal(proposal_id);
+        let mut proposal = load_pending_proposal(&env, &proposal_key)?;
+        let account = load_account(&env, &CustodyBook::Account(proposal.vault_id))?;
+
+        if !account.signers.contains_key(signer.clone()) {
+            return Err(CustodyError::NotASigner.into());
+        }
+
+        require_role(&account, &signer, Role::Approver)?;
+
+        if proposal.approvals.contains(&signer) {
+            return Err(CustodyError::AlreadyApproved.into());
+        }
+
+        proposal.approvals.push_back(signer.clone());
+        let weight = approval_weight(&account, &proposal.approvals);
+        let value = policy_value(&env, &account, &proposal.token, proposal.amount)?;
+        let (threshold, _) = approval_rule(&account, value);
+        if proposal.queued_at.is_none() && weight >= threshold {
+            proposal.queued_at = Some(env.ledger().timestamp());
+        }
+        save_proposal(&env, &proposal_key, &proposal);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("approved"),
+            &account,
+            (proposal_id, signer, weight),
+        );
+
+        log!(
+            &env,
+            "🖊 Proposal {} approved ({}/{})",
+            proposal_id,
+            weight,
+            threshold
+        );
+        Ok(())
+    }
+
+    /// Withdraw a previously given approval from a pending proposal.
+    ///
+    /// Dropping below the threshold takes the proposal out of the queue, so
+    /// the delay restarts once it is approved again.
+    ///
+    /// Requirements:
+    /// - `signer` must have approved the proposal and must authorize the call.
+    pub fn revoke_approval(
+        env: Env,
+        proposal_id: u64,
+        signer: Address,
+    ) -> Result<(), CustodyError> {
+        signer.require_auth();
+
+        let proposal_key = CustodyBook::Proposal(proposal_id);
+        let mut proposal = load_pending_proposal(&env, &proposal_key)?;
+        let account = load_account(&env, &CustodyBook::Account(proposal.vault_id))?;
+
+        let index = proposal
+            .approvals
+            .first_index_of(&signer)
+            .ok_or(CustodyError::NotApproved)?;
+
+        proposal.approvals.remove(index);
+        let weight = approval_weight(&account, &proposal.approvals);
+        // Without a price the threshold is unknown, so leave the queue to be
+        // safe; revoking must not depend on the oracle.
+        let threshold = match policy_value(&env, &account, &proposal.token, proposal.amount) {
+            Ok(value) => approval_rule(&account, value).0,
+            Err(_) => highest_threshold(&account),
+        };
+        if weight < threshold {
+            proposal.queued_at = None;
+        }
+        save_proposal(&env, &proposal_key, &proposal);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("revoked"),
+            &account,
+            (proposal_id, signer, weight),
+        );
+
+        log!(&env, "↩️ Approval revoked on proposal {}", proposal_id);
+        Ok(())
+    }
+
+    /// Execute a withdrawal proposal once it has enough approvals.
+    ///
+    /// Anyone may trigger execution; the approvals recorded on-chain are the
+    /// authorization.
+    ///
+    /// Requirements:
+    /// - The proposal must be pending.
+    /// - The approvals' combined weight must meet the threshold for the
+    ///   proposal's amount, priced at execution.
+    /// - The proposal must be queued, and the withdrawal delay for that price
+    ///   must have passed since it was.
+    /// - Sufficient balance must exist.
+    pub fn execute_withdrawal(env: Env, proposal_id: u64) -> Result<(), Error> {
+        let proposal_key = CustodyBook::Proposal(proposal_id);
+        let mut proposal = load_pending_proposal(&env, &proposal_key)?;
+
+        let account_key = CustodyBook::Account(proposal.vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let value = policy_value(&env, &account, &proposal.token, proposal.amount)?;
+        let (threshold, delay) = approval_rule(&account, value);
+        if approval_weight(&account, &proposal.approvals) < threshold {
+            return Err(CustodyError::InsufficientApprovals.into());
+        }
+
+        let queued_at = proposal
+            .queued_at
+            .ok_or(CustodyError::WithdrawalNotQueued)?;
+        if env.ledger().timestamp() < queued_at.saturating_add(delay) {
+            return Err(CustodyError::WithdrawalLocked.into());
+        }
+
+        proposal.status = ProposalStatus::Executed;
+        save_proposal(&env, &proposal_key, &proposal);
+
+        pay_out(
+            &env,
+            &account_key,
+            &mut account,
+            &proposal.token,
+            &proposal.to,
+            proposal.amount,
+            value,
+        )?;
+
+        publish_vault_event(
+            &env,
+            symbol_short!("executed"),
+            &account,
+            (proposal_id, proposal.token, proposal.to, proposal.amount),
+        );
+
+        log!(&env, "✅ Proposal {} executed", proposal_id);
+        Ok(())
+    }
+
+    /// Cancel a pending withdrawal, e.g. during its time-lock window.
+    ///
+    /// Requirements:
+    /// - `caller` must be the proposer or hold the `Approver` or `Admin` role,
+    ///   and must authorize the call.
+    /// - The proposal must still be pending.
+    pub fn cancel_withdrawal(
+        env: Env,
+        proposal_id: u64,
+        caller: Address,
+    ) -> Result<(), CustodyError> {
+        caller.require_auth();
+
+        let proposal_key = CustodyBook::Proposal(proposal_id);
+        let mut proposal = load_pending_proposal(&env, &proposal_key)?;
+        let account = load_account(&env, &CustodyBook::Account(proposal.vault_id))?;
+
+        if caller != proposal.proposer
+            && !has_role(&account, &caller, Role::Approver)
+            && !has_role(&account, &caller, Role::Admin)
+        {
+            return Err(CustodyError::NotAuthorizedToCancel);
+        }
+
+        proposal.status = ProposalStatus::Cancelled;
+        save_proposal(&env, &proposal_key, &proposal);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("cancelled"),
+            &account,
+            (proposal_id, caller),
+        );
+
+        log!(&env, "🛑 Proposal {} cancelled", proposal_id);
+        Ok(())
+    }
+
+    /// Set the insurance committee and premium terms.
+    ///
+    /// Requirements:
+    /// - `admins` must be a quorum of the admin set and authorize the call.
+    /// - `committee` must be unique with `threshold` between 1 and its size.
+    /// - `premium_bps` must not exceed 10 000 and `period` must be positive.
+    pub fn configure_insurance(
+        env: Env,
+        committee: Vec<Address>,
+        threshold: u32,
+        premium_bps: u32,
+        period: u64,
+        admins: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_admins(&env, &admins)?;
+
+        if threshold == 0
+            || committee.len() < threshold
+            || has_duplicates(&committee)
+            || i128::from(premium_bps) > BPS_DENOMINATOR
+            || period == 0
+        {
+            return Err(CustodyError::InvalidInsuranceConfig);
+        }
+
+        let config = InsuranceConfig {
+            committee,
+            threshold,
+            premium_bps,
+            period,
+        };
+        env.storage().instance().set(&INSURANCE_CONFIG, &config);
+        bump_instance(&env);
+
+        publish_event(
+            &env,
+            symbol_short!("ins_cfg"),
+            &env.current_contract_address(),
+            (config.threshold, config.premium_bps, config.period),
+        );
+        Ok(())
+    }
+
+    /// Set the coverage cap of an insured account for one token.
+    ///
+    /// Coverage starts inactive; it becomes active once a premium is collected.
+    /// Since premiums are taken from the account, the account agrees to the
+    /// cap as well as the committee.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - `committee` must be a quorum of the insurance committee and
+    ///   authorize the call.
+    /// - The account must be insured and `cap` non-negative.
+    pub fn set_coverage(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        token: Address,
+        cap: i128,
+        signers: Vec<Address>,
+        committee: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_committee(&env, &committee)?;
+
+        if cap < 0 {
+            return Err(CustodyError::InvalidAmount);
+        }
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        if !account.is_insured {
+            return Err(CustodyError::NotInsured);
+        }
+
+        let mut coverage = account.coverage.get(token.clone()).unwrap_or(Coverage {
+            cap: 0,
+            paid_until: 0,
+            claimed: 0,
+        });
+        coverage.cap = cap;
+        account.coverage.set(token.clone(), coverage);
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("coverage"), &account, (token, cap));
+        Ok(())
+    }
+
+    /// End the account's coverage for `token`, which stops further premiums.
+    ///
+    /// Premiums already paid stay in the pool and claims filed earlier keep
+    /// their status.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`; the committee does not
+    ///   need to agree.
+    /// - The account must have coverage for `token`.
+    pub fn end_coverage(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        token: Address,
+        signers: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        if account.coverage.remove(token.clone()).is_none() {
+            return Err(CustodyError::NotInsured);
+        }
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(&env, symbol_short!("cov_end"), &account, token);
+        Ok(())
+    }
+
+    /// Take the next premium for `token` coverage from the account balance
+    /// into the insurance pool, extending coverage by one period.
+    ///
+    /// Anyone may call this once the current period has run out, so keepers
+    /// can collect premiums on schedule.
+    ///
+    /// Requirements:
+    /// - The account must have coverage for `token` and the premium must be due.
+    /// - The account must hold enough of `token` to pay the premium.
+    pub fn collect_premium(env: Env, vault_id: u64, token: Address) -> Result<i128, CustodyError> {
+        let config = load_insurance_config(&env)?;
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let mut coverage = account
+            .coverage
+            .get(token.clone())
+            .ok_or(CustodyError::NotInsured)?;
+
+        let now = env.ledger().timestamp();
+        if now < coverage.paid_until {
+            return Err(CustodyError::PremiumNotDue);
+        }
+
+        let premium = coverage.cap * i128::from(config.premium_bps) / BPS_DENOMINATOR;
+        let balance = balance_of(&account, &token);
+        if balance < premium {
+            return Err(CustodyError::InsufficientBalance);
+        }
+
+        coverage.paid_until = now
+            .checked_add(config.period)
+            .ok_or(CustodyError::InvalidInsuranceConfig)?;
+        account.balances.set(token.clone(), balance - premium);
+        account.coverage.set(token.clone(), coverage.clone());
+        save_account(&env, &account_key, &account);
+
+        let pool_key = CustodyBook::InsurancePool(token.clone());
+        let pool = load_pool(&env, &pool_key) + premium;
+        save_pool(&env, &pool_key, pool);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("premium"),
+            &account,
+            (token, premium, coverage.paid_until),
+        );
+        Ok(premium)
+    }
+
+    /// File a claim against the insurance pool. Returns the claim ID.
+    ///
+    /// Requirements:
+    /// - Caller must be the account owner.
+    /// - Coverage for `token` must be active and have `amount` left.
+    pub fn file_claim(
+        env: Env,
+        vault_id: u64,
+        token: Address,
+        amount: i128,
+        evidence: BytesN<32>,
+    ) -> Result<u64, CustodyError> {
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount);
+        }
+
+        let account = load_active_account(&env, &CustodyBook::Account(vault_id))?;
+        account.owner.require_auth();
+        check_coverage(&env, &account, &token, amount)?;
+
+        let id: u64 = env.storage().instance().get(&NEXT_CLAIM_ID).unwrap_or(0);
+        env.storage().instance().set(&NEXT_CLAIM_ID, &(id + 1));
+
+        let claim = InsuranceClaim {
+            id,
+            vault_id,
+            token: token.clone(),
+            amount,
+            evidence,
+            status: ClaimStatus::Filed,
+        };
+        save_claim(&env, &CustodyBook::Claim(id), &claim);
+
+        publish_vault_event(&env, symbol_short!("claim"), &account, (id, token, amount));
+        Ok(id)
+    }
+
+    /// Approve a filed claim and pay it from the pool into the account.
+    ///
+    /// Requirements:
+    /// - `committee` must be a quorum of the insurance committee and
+    ///   authorize the call.
+    /// - Coverage must still be active with enough room for the claim.
+    /// - The pool must hold enough of the claimed token.
+    pub fn approve_claim(
+        env: Env,
+        claim_id: u64,
+        committee: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_committee(&env, &committee)?;
+
+        let claim_key = CustodyBook::Claim(claim_id);
+        let mut claim = load_filed_claim(&env, &claim_key)?;
+
+        let account_key = CustodyBook::Account(claim.vault_id);
+        let mut account = load_account(&env, &account_key)?;
+        let mut coverage = check_coverage(&env, &account, &claim.token, claim.amount)?;
+
+        let pool_key = CustodyBook::InsurancePool(claim.token.clone());
+        let pool = load_pool(&env, &pool_key);
+        if pool < claim.amount {
+            return Err(CustodyError::InsufficientPool);
+        }
+        save_pool(&env, &pool_key, pool - claim.amount);
+
+        coverage.claimed += claim.amount;
+        let balance = balance_of(&account, &claim.token) + claim.amount;
+        account.balances.set(claim.token.clone(), balance);
+        account.coverage.set(claim.token.clone(), coverage);
+        save_account(&env, &account_key, &account);
+
+        claim.status = ClaimStatus::Paid;
+        save_claim(&env, &claim_key, &claim);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("claim_pay"),
+            &account,
+            (claim_id, claim.token, claim.amount),
+        );
+        Ok(())
+    }
+
+    /// Reject a filed claim.
+    ///
+    /// Requirements:
+    /// - `committee` must be a quorum of the insurance committee and
+    ///   authorize the call.
+    pub fn reject_claim(
+        env: Env,
+        claim_id: u64,
+        committee: Vec<Address>,
+    ) -> Result<(), CustodyError> {
+        require_committee(&env, &committee)?;
+
+        let claim_key = CustodyBook::Claim(claim_id);
+        let mut claim = load_filed_claim(&env, &claim_key)?;
+
+        claim.status = ClaimStatus::Rejected;
+        save_claim(&env, &claim_key, &claim);
+
+        let account = load_account(&env, &CustodyBook::Account(claim.vault_id))?;
+        publish_vault_event(&env, symbol_short!("claim_rej"), &account, claim_id);
+        Ok(())
+    }
+
+    /// Lock `amount` of `token` from the vault in a hash-time-locked escrow
+    /// for `beneficiary`. Returns the escrow ID.
+    ///
+    /// The escrow expires `timeout` seconds from now. The funds leave the
+    /// vault's balance immediately and count toward its velocity limits.
+    ///
+    /// Requirements:
+    /// - Same authorization as `withdraw_assets`.
+    /// - No withdrawal delay may apply to `amount`.
+    /// - `beneficiary` must be an active allowlisted destination.
+    /// - `timeout` must be positive and must not overflow the ledger clock.
+    pub fn lock_escrow(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        token: Address,
+        amount: i128,
+        beneficiary: Address,
+        hashlock: BytesN<32>,
+        timeout: u64,
+        signers: Vec<Address>,
+    ) -> Result<u64, Error> {
+        if amount <= 0 {
+            return Err(CustodyError::InvalidAmount.into());
+        }
+
+        let expires_at = match env.ledger().timestamp().checked_add(timeout) {
+            Some(expires_at) if timeout > 0 => expires_at,
+            _ => return Err(EscrowError::InvalidTimeout.into()),
+        };
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let value = policy_value(&env, &account, &token, amount)?;
+        let (threshold, delay) = approval_rule(&account, value);
+        require_caller_and_signers(&account, &caller, Role::Proposer, &signers, threshold)?;
+
+        if delay > 0 {
+            return Err(CustodyError::WithdrawalDelayActive.into());
+        }
+
+        check_destination(&env, &account, &beneficiary)?;
+        debit(&env, &mut account, &token, amount, value)?;
+        save_account(&env, &account_key, &account);
+
+        let id: u64 = env.storage().instance().get(&NEXT_ESCROW_ID).unwrap_or(0);
+        env.storage().instance().set(&NEXT_ESCROW_ID, &(id + 1));
+
+        let escrow = Escrow {
+            id,
+            vault_id,
+            token: token.clone(),
+            amount,
+            beneficiary: beneficiary.clone(),
+            hashlock: hashlock.clone(),
+            expires_at,
+            status: EscrowStatus::Locked,
+        };
+        save_escrow(&env, &CustodyBook::Escrow(id), &escrow);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("esc_lock"),
+            &account,
+            (id, token, amount, beneficiary, hashlock, escrow.expires_at),
+        );
+        Ok(id)
+    }
+
+    /// Release a locked escrow to its beneficiary by revealing the preimage
+    /// of its hashlock.
+    ///
+    /// Anyone may submit the preimage; it is published in the `esc_claim`
+    /// event so the counterparty can settle the other leg.
+    ///
+    /// Requirements:
+    /// - The escrow must be locked and not yet expired.
+    /// - `sha256(preimage)` must equal the hashlock.
+    /// - The contract must not be paused.
+    pub fn claim_escrow(env: Env, escrow_id: u64, preimage: Bytes) -> Result<(), Error> {
+        if env.storage().instance().has(&PAUSED) {
+            return Err(CustodyError::ContractPaused.into());
+        }
+
+        let escrow_key = CustodyBook::Escrow(escrow_id);
+        let mut escrow = load_locked_escrow(&env, &escrow_key)?;
+
+        if env.ledger().timestamp() >= escrow.expires_at {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        let digest: BytesN<32> = env.crypto().sha256(&preimage).into();
+        if digest != escrow.hashlock {
+            return Err(EscrowError::InvalidPreimage.into());
+        }
+
+        escrow.status = EscrowStatus::Claimed;
+        save_escrow(&env, &escrow_key, &escrow);
+
+        token::Client::new(&env, &escrow.token).transfer(
+            &env.current_contract_address(),
+            &escrow.beneficiary,
+            &escrow.amount,
+        );
+
+        let account = load_account(&env, &CustodyBook::Account(escrow.vault_id))?;
+        publish_vault_event(
+            &env,
+            symbol_short!("esc_claim"),
+            &account,
+            (escrow_id, escrow.beneficiary, preimage),
+        );
+        Ok(())
+    }
+
+    /// Return an expired escrow's funds to its vault.
+    ///
+    /// Anyone may trigger the refund once the escrow has expired. It does not
+    /// reset the vault's velocity window.
+    ///
+    /// Requirements:
+    /// - The escrow must be locked and expired.
+    pub fn refund_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let escrow_key = CustodyBook::Escrow(escrow_id);
+        let mut escrow = load_locked_escrow(&env, &escrow_key)?;
+
+        if env.ledger().timestamp() < escrow.expires_at {
+            return Err(EscrowError::EscrowNotExpired.into());
+        }
+
+        escrow.status = EscrowStatus::Refunded;
+        save_escrow(&env, &escrow_key, &escrow);
+
+        let account_key = CustodyBook::Account(escrow.vault_id);
+        let mut account = load_account(&env, &account_key)?;
+        let balance = balance_of(&account, &escrow.token) + escrow.amount;
+        account.balances.set(escrow.token.clone(), balance);
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("esc_rfnd"),
+            &account,
+            (escrow_id, escrow.token, escrow.amount, balance),
+        );
+        Ok(())
+    }
+
+    /// Grant `total` of `token` from the vault to `beneficiary`, vesting
+    /// linearly from `start` to `end` with a `cliff`. Returns the schedule ID.
+    ///
+    /// All three are ledger timestamps. The grant leaves the vault's balance
+    /// immediately and counts toward its velocity limits.
+    ///
+    /// Requirements:
+    /// - Same authorization as `withdraw_assets`.
+    /// - No withdrawal delay may apply to `total`.
+    /// - `beneficiary` must be an active allowlisted destination.
+    /// - `start <= cliff <= end` and `start < end`.
+    pub fn create_vesting(
+        env: Env,
+        vault_id: u64,
+        caller: Address,
+        beneficiary: Address,
+        token: Address,
+        total: i128,
+        cliff: u64,
+        start: u64,
+        end: u64,
+        signers: Vec<Address>,
+    ) -> Result<u64, Error> {
+        if total <= 0 {
+            return Err(CustodyError::InvalidAmount.into());
+        }
+
+        if start >= end || cliff < start || cliff > end {
+            return Err(VestingError::InvalidSchedule.into());
+        }
+
+        let account_key = CustodyBook::Account(vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+
+        let value = policy_value(&env, &account, &token, total)?;
+        let (threshold, delay) = approval_rule(&account, value);
+        require_caller_and_signers(&account, &caller, Role::Proposer, &signers, threshold)?;
+
+        if delay > 0 {
+            return Err(CustodyError::WithdrawalDelayActive.into());
+        }
+
+        check_destination(&env, &account, &beneficiary)?;
+        debit(&env, &mut account, &token, total, value)?;
+        save_account(&env, &account_key, &account);
+
+        let id: u64 = env.storage().instance().get(&NEXT_VESTING_ID).unwrap_or(0);
+        env.storage().instance().set(&NEXT_VESTING_ID, &(id + 1));
+
+        let schedule = VestingSchedule {
+            id,
+            vault_id,
+            beneficiary: beneficiary.clone(),
+            token: token.clone(),
+            total,
+            claimed: 0,
+            cliff,
+            start,
+            end,
+            revoked: false,
+        };
+        save_vesting(&env, &CustodyBook::Vesting(id), &schedule);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("vesting"),
+            &account,
+            (id, beneficiary, token, total, cliff, start, end),
+        );
+        Ok(id)
+    }
+
+    /// Pay out everything vested so far and not yet claimed. Returns the
+    /// amount paid.
+    ///
+    /// Requirements:
+    /// - Caller must be the beneficiary.
+    /// - Something must have vested since the last claim.
+    /// - The contract must not be paused.
+    pub fn claim_vested(env: Env, vesting_id: u64) -> Result<i128, Error> {
+        if env.storage().instance().has(&PAUSED) {
+            return Err(CustodyError::ContractPaused.into());
+        }
+
+        let vesting_key = CustodyBook::Vesting(vesting_id);
+        let mut schedule = load_vesting(&env, &vesting_key)?;
+        schedule.beneficiary.require_auth();
+
+        let amount = vested_amount(&schedule, env.ledger().timestamp()) - schedule.claimed;
+        if amount <= 0 {
+            return Err(VestingError::NothingToClaim.into());
+        }
+
+        schedule.claimed += amount;
+        save_vesting(&env, &vesting_key, &schedule);
+
+        token::Client::new(&env, &schedule.token).transfer(
+            &env.current_contract_address(),
+            &schedule.beneficiary,
+            &amount,
+        );
+
+        let account = load_account(&env, &CustodyBook::Account(schedule.vault_id))?;
+        publish_vault_event(
+            &env,
+            symbol_short!("vest_clm"),
+            &account,
+            (vesting_id, amount, schedule.claimed),
+        );
+        Ok(amount)
+    }
+
+    /// Stop a vesting schedule and return its unvested part to the vault.
+    /// Returns the amount returned.
+    ///
+    /// Whatever has vested by now stays claimable by the beneficiary.
+    ///
+    /// Requirements:
+    /// - Same authorization as `set_withdrawal_delay`.
+    /// - The schedule must not already be revoked.
+    pub fn revoke_vesting(
+        env: Env,
+        vesting_id: u64,
+        caller: Address,
+        signers: Vec<Address>,
+    ) -> Result<i128, Error> {
+        let vesting_key = CustodyBook::Vesting(vesting_id);
+        let mut schedule = load_vesting(&env, &vesting_key)?;
+        if schedule.revoked {
+            return Err(VestingError::VestingRevoked.into());
+        }
+
+        let account_key = CustodyBook::Account(schedule.vault_id);
+        let mut account = load_active_account(&env, &account_key)?;
+        require_caller_and_signers(
+            &account,
+            &caller,
+            Role::Admin,
+            &signers,
+            account.required_signatures,
+        )?;
+
+        let now = env.ledger().timestamp();
+        let vested = vested_amount(&schedule, now);
+        let refunded = schedule.total - vested;
+
+        schedule.total = vested;
+        schedule.end = schedule.end.min(now);
+        schedule.revoked = true;
+        save_vesting(&env, &vesting_key, &schedule);
+
+        let balance = balance_of(&account, &schedule.token) + refunded;
+        account.balances.set(schedule.token.clone(), balance);
+        save_account(&env, &account_key, &account);
+
+        publish_vault_event(
+            &env,
+            symbol_short!("vest_rvk"),
+            &account,
+            (vesting_id, refunded),
+        );
+        Ok(refunded)
+    }
+
+    /// View the recovery in progress for an account.
+    pub fn view_recovery(env: Env, vault_id: u64) -> Result<PendingRecovery, CustodyError> {
+        load_recovery(&env, &CustodyBook::Recovery(vault_id))
+    }
+
+    /// View the inheritance claim in progress for an account.
+    pub fn view_inheritance(
+        env: Env,
+        vault_id: u64,
+    ) -> Result<PendingInheritance, InheritanceError> {
+        load_inheritance(&env, &CustodyBook::Inheritance(vault_id))
+    }
+
+    /// View an insurance claim.
+    pub fn view_claim(env: Env, claim_id: u64) -> Result<InsuranceClaim, CustodyError> {
+        env.storage()
+            .persistent()
+            .get(&CustodyBook::Claim(claim_id))
+            .ok_or(CustodyError::ClaimNotFound)
+    }
+
+    /// View an escrow.
+    pub fn view_escrow(env: Env, escrow_id: u64) -> Result<Escrow, EscrowError> {
+        env.storage()
+            .persistent()
+            .get(&CustodyBook::Escrow(escrow_id))
+            .ok_or(EscrowError::EscrowNotFound)
+    }
+
+    /// View a vesting schedule.
+    pub fn view_vesting(env: Env, vesting_id: u64) -> Result<VestingSchedule, VestingError> {
+        load_vesting(&env, &CustodyBook::Vesting(vesting_id))
+    }
+
+    /// Amount of `token` held by the insurance pool.
+    pub fn view_insurance_pool(env: Env, token: Address) -> i128 {
+        load_pool(&env, &CustodyBook::InsurancePool(token))
+    }
+
+    /// View a withdrawal proposal.
+    ///
+    /// Requirements:
+    /// - `viewer` must hold a role in the proposal's account and authorize
+    ///   the call.
+    pub fn view_proposal(
+        env: Env,
+        proposal_id: u64,
+        viewer: Address,
+    ) -> Result<WithdrawalProposal, CustodyError> {
+        let proposal: WithdrawalProposal = env
+            .storage()
+            .persistent()
+            .get(&CustodyBook::Proposal(proposal_id))
+            .ok_or(CustodyError::ProposalNotFound)?;
+
+        let account = load_account(&env, &CustodyBook::Account(proposal.vault_id))?;
+        require_member(&account, &viewer)?;
+
+        Ok(proposal)
+    }
+
+    /// IDs of the custody vaults owned by `owner`, oldest first.
+    pub fn list_vaults(env: Env, owner: Address) -> Vec<u64> {
+        load_vault_ids(&env, &owner)
+    }
+
+    /// View custody account details, including the per-token `balances` and
+    /// insurance `coverage`.
+    ///
+    /// The role check gates this interface only; ledger entries stay
+    /// readable to anyone inspecting contract storage.
+    ///
+    /// Requirements:
+    /// - `viewer` must hold a role in the account (e.g. `Auditor`) and
+    ///   authorize the call.
+    pub fn view_custody_account(
+        env: Env,
+        vault_id: u64,
+        viewer: Address,
+    ) -> Result<CustodyAccount, CustodyError> {
+        let account = load_account(&env, &CustodyBook::Account(vault_id))?;
+        require_member(&account, &viewer)?;
+        Ok(account)
+    }
+
+    /// View the custody balance of a single token.
+    ///
+    /// Requirements:
+    /// - Same authorization as `view_custody_account`.
+    pub fn view_balance(
+        env: Env,
+        vault_id: u64,
+        token: Address,
+        viewer: Address,
+    ) -> Result<i128, CustodyError> {
+        let account = load_account(&env, &CustodyBook::Account(vault_id))?;
+        require_member(&account, &viewer)?;
+        Ok(balance_of(&account, &token))
+    }
+
+    /// Extend the lifetime of a custody account and the entries keyed by
+    /// it: the owner's vault index and any recovery or inheritance claim.
+    ///
+    /// Anyone may call this, so keepers can stop dormant accounts from being
+    /// archived. `extend_to` is the desired TTL in ledgers and is capped at
+    /// the network's maximum. Use `extend_entry_ttls` for the account's
+    /// proposals, escrows, vesting schedules and claims.
+    ///
+    /// Requirements:
+    /// - The account must exist.
+    /// - `extend_to` must be positive.
+    pub fn extend_account_ttl(env: Env, vault_id: u64, extend_to: u32) -> Result<(), CustodyError> {
+        if extend_to == 0 {
+            return Err(CustodyError::InvalidTtl);
+        }
+
+        let account = load_account(&env, &CustodyBook::Account(vault_id))?;
+        let extend_to = extend_to.min(env.storage().max_ttl());
+        for key in [
+            CustodyBook::Account(vault_id),
+            CustodyBook::OwnerVaults(account.owner),
+            CustodyBook::Recovery(vault_id),
+            CustodyBook::Inheritance(vault_id),
+        ] {
+            if env.storage().persistent().has(&key) {
+                env.storage()
+                    .persistent()
+                    .extend_ttl(&key, extend_to, extend_to);
+            }
+        }
+        bump_instance(&env);
+
+        Ok(())
+    }
+
+    /// Extend the lifetime of arbitrary custody entries, e.g. long-running
+    /// proposals, escrows, vesting schedules, claims or insurance pools.
+    ///
+    /// Anyone may call this. Keys without a stored entry are skipped.
+    ///
+    /// Requirements:
+    /// - `extend_to` must be positive.
+    pub fn extend_entry_ttls(
+        env: Env,
+        keys: Vec<CustodyBook>,
+        extend_to: u32,
+    ) -> Result<(), CustodyError> {
+        if extend_to == 0 {
+            return Err(CustodyError::InvalidTtl);
+        }
+
+        let extend_to = extend_to.min(env.storage().max_ttl());
+        for key in keys.iter() {
+            if env.storage().persistent().has(&key) {
+                env.storage()
+                    .persistent()
+                    .extend_ttl(&key, extend_to, extend_to);
+            }
+        }
+        bump_instance(&env);
+
+        Ok(())
+    }
+
+    /// Storage lifetimes applied on every write.
+    pub fn view_ttl_config(env: Env) -> TtlConfig {
+        load_ttl_config(&env)
+    }
+
+    /// View the contract admin set.
+    pub fn view_admins(env: Env) -> Result<AdminConfig, CustodyError> {
+        load_admin_config(&env)
+    }
+
+    /// Reason code of the active emergency pause, or `None` if not paused.
+    pub fn paused(env: Env) -> Option<u32> {
+        env.storage().instance().get(&PAUSED)
+    }
+
+    /// Storage schema version the stored data is laid out in.
+    pub fn schema_version(env: Env) -> u32 {
+        env.storage().instance().get(&SCHEMA_VERSION).unwrap_or(0)
+    }
+
+    /// Get total number of custody accounts created (global metric).
+    pub fn total_accounts(env: Env) -> u64 {
+        env.storage().instance().get(&TOTAL_ACCOUNTS).unwrap_or(0)
+    }
+}
+
+// ----------------------------------------------------------
+// 🗄️ Storage Helpers
+// ----------------------------------------------------------
+
+/// Load an account, with any withdrawal delay reduction that has taken
+/// effect applied.
+fn load_account(env: &Env, account_key: &CustodyBook) -> Result<CustodyAccount, CustodyError> {
+    let mut account: CustodyAccount = env
+        .storage()
+        .persistent()
+        .get(account_key)
+        .ok_or(CustodyError::AccountNotFound)?;
+    settle_withdrawal_delay(env, &mut account);
+    Ok(account)
+}
+
+/// Persist an account in its own persistent entry and keep it (and the
+/// contract instance) alive.
+fn save_account(env: &Env, account_key: &CustodyBook, account: &CustodyAccount) {
+    env.storage().persistent().set(account_key, account);
+    extend_persistent(env, account_key);
+    bump_instance(env);
+}
+
+fn load_vault_ids(env: &Env, owner: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&CustodyBook::OwnerVaults(owner.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn save_vault_ids(env: &Env, owner: &Address, vault_ids: &Vec<u64>) {
+    let key = CustodyBook::OwnerVaults(owner.clone());
+    env.storage().persistent().set(&key, vault_ids);
+    extend_persistent(env, &key);
+    bump_instance(env);
+}
+
+fn save_proposal(env: &Env, proposal_key: &CustodyBook, proposal: &WithdrawalProposal) {
+    env.storage().persistent().set(proposal_key, proposal);
+    extend_persistent(env, proposal_key);
+    bump_instance(env);
+}
+
+fn load_admin_config(env: &Env) -> Result<AdminConfig, CustodyError> {
+    env.storage()
+        .instance()
+        .get(&ADMIN_CONFIG)
+        .ok_or(CustodyError::NotInitialized)
+}
+
+fn load_insurance_config(env: &Env) -> Result<InsuranceConfig, CustodyError> {
+    env.storage()
+        .instance()
+        .get(&INSURANCE_CONFIG)
+        .ok_or(CustodyError::InsuranceNotConfigured)
+}
+
+fn load_pool(env: &Env, pool_key: &CustodyBook) -> i128 {
+    env.storage().persistent().get(pool_key).unwrap_or(0)
+}
+
+fn save_pool(env: &Env, pool_key: &CustodyBook, amount: i128) {
+    env.storage().persistent().set(pool_key, &amount);
+    extend_persistent(env, pool_key);
+    bump_instance(env);
+}
+
+fn save_claim(env: &Env, claim_key: &CustodyBook, claim: &InsuranceClaim) {
+    env.storage().persistent().set(claim_key, claim);
+    extend_persistent(env, claim_key);
+    bump_instance(env);
+}
+
+fn save_recovery(env: &Env, recovery_key: &CustodyBook, recovery: &PendingRecovery) {
+    env.storage().persistent().set(recovery_key, recovery);
+    extend_persistent(env, recovery_key);
+    bump_instance(env);
+}
+
+fn load_recovery(env: &Env, recovery_key: &CustodyBook) -> Result<PendingRecovery, CustodyError> {
+    env.storage()
+        .persistent()
+        .get(recovery_key)
+        .ok_or(CustodyError::RecoveryNotFound)
+}
+
+fn save_inheritance(env: &Env, inheritance_key: &CustodyBook, inheritance: &PendingInheritance) {
+    env.storage().persistent().set(inheritance_key, inheritance);
+    extend_persistent(env, inheritance_key);
+    bump_instance(env);
+}
+
+fn load_inheritance(
+    env: &Env,
+    inheritance_key: &CustodyBook,
+) -> Result<PendingInheritance, InheritanceError> {
+    env.storage()
+        .persistent()
+        .get(inheritance_key)
+        .ok_or(InheritanceError::InheritanceNotFound)
+}
+
+/// The account's inheritance claim, unless activity since it started has
+/// defeated it. A defeated claim may still be stored; it no longer blocks a
+/// new claim or a change of heir.
+fn load_live_inheritance(
+    env: &Env,
+    account: &CustodyAccount,
+    inheritance_key: &CustodyBook,
+) -> Option<PendingInheritance> {
+    load_inheritance(env, inheritance_key)
+        .ok()
+        .filter(|inheritance| account.last_activity < inheritance.started_at)
+}
+
+/// Make `new_owner` the owner of the vault and persist it. If the old owner
+/// was also a signer, its weight moves to the new owner, on top of any weight
+/// the new owner already had but capped below `required_signatures`; the old
+/// owner loses every role. Returns the old owner.
+fn hand_over(
+    env: &Env,
+    account_key: &CustodyBook,
+    account: &mut CustodyAccount,
+    new_owner: &Address,
+) -> Address {
+    let old_owner = account.owner.clone();
+    if let Some(weight) = account.signers.get(old_owner.clone()) {
+        account.signers.remove(old_owner.clone());
+        let existing = account.signers.get(new_owner.clone()).unwrap_or(0);
+        let cap = account.required_signatures.saturating_sub(1);
+        account
+            .signers
+            .set(new_owner.clone(), existing.saturating_add(weight).min(cap));
+    }
+    account.roles.remove(old_owner.clone());
+    account.owner = new_owner.clone();
+    save_account(env, account_key, account);
+
+    let mut old_ids = load_vault_ids(env, &old_owner);
+    if let Some(index) = old_ids.first_index_of(account.id) {
+        old_ids.remove(index);
+    }
+    save_vault_ids(env, &old_owner, &old_ids);
+
+    let mut new_ids = load_vault_ids(env, new_owner);
+    new_ids.push_back(account.id);
+    save_vault_ids(env, new_owner, &new_ids);
+
+    old_owner
+}
+
+fn save_escrow(env: &Env, escrow_key: &CustodyBook, escrow: &Escrow) {
+    env.storage().persistent().set(escrow_key, escrow);
+    extend_persistent(env, escrow_key);
+    bump_instance(env);
+}
+
+fn load_locked_escrow(env: &Env, escrow_key: &CustodyBook) -> Result<Escrow, EscrowError> {
+    let escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(escrow_key)
+        .ok_or(EscrowError::EscrowNotFound)?;
+
+    if escrow.status != EscrowStatus::Locked {
+        return Err(EscrowError::EscrowNotLocked);
+    }
+
+    Ok(escrow)
+}
+
+fn save_vesting(env: &Env, vesting_key: &CustodyBook, schedule: &VestingSchedule) {
+    env.storage().persistent().set(vesting_key, schedule);
+    extend_persistent(env, vesting_key);
+    bump_instance(env);
+}
+
+fn load_vesting(env: &Env, vesting_key: &CustodyBook) -> Result<VestingSchedule, VestingError> {
+    env.storage()
+        .persistent()
+        .get(vesting_key)
+        .ok_or(VestingError::VestingNotFound)
+}
+
+fn load_filed_claim(env: &Env, claim_key: &CustodyBook) -> Result<InsuranceClaim, CustodyError> {
+    let claim: InsuranceClaim = env
+        .storage()
+        .persistent()
+        .get(claim_key)
+        .ok_or(CustodyError::ClaimNotFound)?;
+
+    if claim.status != ClaimStatus::Filed {
+        return Err(CustodyError::ClaimNotPending);
+    }
+
+    Ok(claim)
+}
+
+/// Return the account's active coverage for `token` if it can absorb a
+/// claim of `amount`.
+fn check_coverage(
+    env: &Env,
+    account: &CustodyAccount,
+    token: &Address,
+    amount: i128,
+) -> Result<Coverage, CustodyError> {
+    if !account.is_insured {
+        return Err(CustodyError::NotInsured);
+    }
+
+    let coverage = account
+        .coverage
+        .get(token.clone())
+        .ok_or(CustodyError::NotInsured)?;
+
+    if env.ledger().timestamp() >= coverage.paid_until {
+        return Err(CustodyError::CoverageInactive);
+    }
+
+    if coverage.claimed + amount > coverage.cap {
+        return Err(CustodyError::CoverageExceeded);
+    }
+
+    Ok(coverage)
+}
+
+fn next_proposal_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&NEXT_PROPOSAL_ID).unwrap_or(0);
+    env.storage().instance().set(&NEXT_PROPOSAL_ID, &(id + 1));
+    id
+}
+
+fn load_ttl_config(env: &Env) -> TtlConfig {
+    env.storage()
+        .instance()
+        .get(&TTL_CONFIG)
+        .unwrap_or(TtlConfig {
+            instance_threshold: INSTANCE_TTL_THRESHOLD,
+            instance_extend_to: INSTANCE_TTL_EXTEND_TO,
+            persistent_threshold: PERSISTENT_TTL_THRESHOLD,
+            persistent_extend_to: PERSISTENT_TTL_EXTEND_TO,
+        })
+}
+
+/// Keep the contract instance (code and global counters) alive.
+fn bump_instance(env: &Env) {
+    let config = load_ttl_config(env);
+    env.storage()
+        .instance()
+        .extend_ttl(config.instance_threshold, config.instance_extend_to);
+}
+
+/// Keep a persistent entry alive after writing it.
+fn extend_persistent(env: &Env, key: &CustodyBook) {
+    let config = load_ttl_config(env);
+    env.storage().persistent().extend_ttl(
+        key,
+        config.persistent_threshold,
+        config.persistent_extend_to,
+    );
+}
+
+fn load_active_account(
+    env: &Env,
+    account_key: &CustodyBook,
+) -> Result<CustodyAccount, CustodyError> {
+    let account = load_account(env, account_key)?;
+
+    if !account.is_active {
+        return Err(CustodyError::AccountInactive);
+    }
+
+    Ok(account)
+}
+
+fn load_pending_proposal(
+    env: &Env,
+    proposal_key: &CustodyBook,
+) -> Result<WithdrawalProposal, CustodyError> {
+    let proposal: WithdrawalProposal = env
+        .storage()
+        .persistent()
+        .get(proposal_key)
+        .ok_or(CustodyError::ProposalNotFound)?;
+
+    if proposal.status != ProposalStatus::Pending {
+        return Err(CustodyError::ProposalNotPending);
+    }
+
+    Ok(proposal)
+}
+
+// ----------------------------------------------------------
+// 🔐 Signature Helpers
+// ----------------------------------------------------------
+
+/// Check that `signers` are registered signers whose weights reach
+/// `threshold` and demand `require_auth()` from each of them on the current
+/// invocation.
+fn require_signers(
+    account: &CustodyAccount,
+    signers: &Vec<Address>,
+    threshold: u32,
+) -> Result<(), CustodyError> {
+    if has_duplicates(signers) {
+        return Err(CustodyError::DuplicateSigner);
+    }
+
+    let mut weight: u32 = 0;
+    for signer in signers.iter() {
+        let signer_weight = account
+            .signers
+            .get(signer.clone())
+            .ok_or(CustodyError::NotASigner)?;
+        require_role(account, &signer, Role::Approver)?;
+        signer.require_auth();
+        weight = weight.saturating_add(signer_weight);
+    }
+
+    if weight < threshold {
+        return Err(CustodyError::InsufficientSignatures);
+    }
+
+    Ok(())
+}
+
+/// Combined weight of the approvers that are still signers with the
+/// `Approver` role; signers and roles may have changed since they approved.
+fn approval_weight(account: &CustodyAccount, approvals: &Vec<Address>) -> u32 {
+    let mut weight: u32 = 0;
+    for approver in approvals.iter() {
+        if has_role(account, &approver, Role::Approver) {
+            weight = weight.saturating_add(account.signers.get(approver).unwrap_or(0));
+        }
+    }
+    weight
+}
+
+/// Signer weight and delay that a withdrawal worth `value` needs, in the
+/// account's policy unit.
+fn approval_rule(account: &CustodyAccount, value: i128) -> (u32, u64) {
+    let mut threshold = account.required_signatures;
+    let mut delay = account.withdrawal_delay;
+    for tier in account.approval_tiers.iter() {
+        if value >= tier.min_amount {
+            threshold = threshold.max(tier.threshold);
+            delay = delay.max(tier.delay);
+        }
+    }
+    (threshold, delay)
+}
+
+/// Whether `new` keeps every limit of `old` with the same period and no
+/// higher cap, so replacing `old` with it cannot let more out.
+fn tightens_limits(old: &Vec<VelocityLimit>, new: &Vec<VelocityLimit>) -> bool {
+    old.iter().all(|limit| {
+        new.iter()
+            .any(|kept| kept.period == limit.period && kept.cap <= limit.cap)
+    })
+}
+
+/// Whether every amount needs at least the threshold and delay under `new`
+/// that it needs under the account's current tiers.
+fn tightens_tiers(account: &CustodyAccount, new: &Vec<ApprovalTier>) -> bool {
+    account.approval_tiers.iter().all(|tier| {
+        let mut threshold = account.required_signatures;
+        let mut delay = account.withdrawal_delay;
+        for kept in new.iter() {
+            if tier.min_amount >= kept.min_amount {
+                threshold = threshold.max(kept.threshold);
+                delay = delay.max(kept.delay);
+            }
+        }
+        threshold >= tier.threshold && delay >= tier.delay
+    })
+}
+
+/// Largest signer weight any withdrawal of the account can require.
+fn highest_threshold(account: &CustodyAccount) -> u32 {
+    let mut threshold = account.required_signatures;
+    for tier in account.approval_tiers.iter() {
+        threshold = threshold.max(tier.threshold);
+    }
+    threshold
+}
+
+fn total_weight(signers: &Map<Address, u32>) -> u32 {
+    let mut weight: u32 = 0;
+    for signer_weight in signers.values().iter() {
+        weight = weight.saturating_add(signer_weight);
+    }
+    weight
+}
+
+/// Check that `admins` is a quorum of the contract admin set and demand
+/// `require_auth()` from each of them on the current invocation.
+fn require_admins(env: &Env, admins: &Vec<Address>) -> Result<AdminConfig, CustodyError> {
+    let config = load_admin_config(env)?;
+    require_quorum(
+        &config.admins,
+        config.threshold,
+        admins,
+        CustodyError::NotAnAdmin,
+    )?;
+    Ok(config)
+}
+
+/// Same as `require_admins`, against the insurance committee.
+fn require_committee(env: &Env, committee: &Vec<Address>) -> Result<InsuranceConfig, CustodyError> {
+    let config = load_insurance_config(env)?;
+    require_quorum(
+        &config.committee,
+        config.threshold,
+        committee,
+        CustodyError::NotOnCommittee,
+    )?;
+    Ok(config)
+}
+
+fn require_quorum(
+    members: &Vec<Address>,
+    threshold: u32,
+    signers: &Vec<Address>,
+    not_member: CustodyError,
+) -> Result<(), CustodyError> {
+    if has_duplicates(signers) {
+        return Err(CustodyError::DuplicateSigner);
+    }
+
+    for signer in signers.iter() {
+        if !members.contains(&signer) {
+            return Err(not_member);
+        }
+        signer.require_auth();
+    }
+
+    if signers.len() < threshold {
+        return Err(CustodyError::InsufficientSignatures);
+    }
+
+    Ok(())
+}
+
+/// Demand `caller`'s authorization and `role` plus a signer quorum, as
+/// needed for withdrawals and policy changes.
+fn require_caller_and_signers(
+    account: &CustodyAccount,
+    caller: &Address,
+    role: Role,
+    signers: &Vec<Address>,
+    threshold: u32,
+) -> Result<(), CustodyError> {
+    require_role(account, caller, role)?;
+
+    // A caller listed in `signers` is authorized there; asking twice would abort.
+    if !signers.contains(caller) {
+        caller.require_auth();
+    }
+
+    require_signers(account, signers, threshold)
+}
+
+/// The owner holds every role; everyone else holds what `roles` grants.
+fn has_role(account: &CustodyAccount, member: &Address, role: Role) -> bool {
+    *member == account.owner
+        || account
+            .roles
+            .get(member.clone())
+            .is_some_and(|roles| roles.contains(role))
+}
+
+fn require_role(
+    account: &CustodyAccount,
+    member: &Address,
+    role: Role,
+) -> Result<(), CustodyError> {
+    if !has_role(account, member, role) {
+        return Err(CustodyError::MissingRole);
+    }
+    Ok(())
+}
+
+/// Demand the authorization of `member`, who must be the owner or hold at
+/// least one role in the account.
+fn require_member(account: &CustodyAccount, member: &Address) -> Result<(), CustodyError> {
+    if *member != account.owner && !account.roles.contains_key(member.clone()) {
+        return Err(CustodyError::MissingRole);
+    }
+    member.require_auth();
+    Ok(())
+}
+
+fn grant_role(env: &Env, account: &mut CustodyAccount, member: &Address, role: Role) {
+    let mut roles = account.roles.get(member.clone()).unwrap_or(Vec::new(env));
+    if !roles.contains(role) {
+        roles.push_back(role);
+        account.roles.set(member.clone(), roles);
+    }
+}
+
+fn has_duplicates(addresses: &Vec<Address>) -> bool {
+    for (i, address) in addresses.iter().enumerate() {
+        if addresses.last_index_of(&address) != Some(i as u32) {
+            return true;
+        }
+    }
+    false
+}
+
+// ----------------------------------------------------------
+// 🏦 Payout Helpers
+// ----------------------------------------------------------
+
+fn balance_of(account: &CustodyAccount, token: &Address) -> i128 {
+    account.balances.get(token.clone()).unwrap_or(0)
+}
+
+/// Pull `amount` of `token` from `from` into the contract and credit it to
+/// the account. Returns the new balance.
+fn take_deposit(
+    env: &Env,
+    account_key: &CustodyBook,
+    account: &mut CustodyAccount,
+    from: &Address,
+    token: &Address,
+    amount: i128,
+) -> i128 {
+    token::Client::new(env, token).transfer(from, &env.current_contract_address(), &amount);
+
+    let balance = balance_of(account, token) + amount;
+    account.balances.set(token.clone(), balance);
+    save_account(env, account_key, account);
+    balance
+}
+
+/// Check the outflow policies (pause, allowlist, velocity limits), debit
+/// `amount` of `token` from the account, persist it and send the tokens to `to`.
+///
+/// `value` is `amount` in the account's policy unit (see `policy_value`).
+fn pay_out(
+    env: &Env,
+    account_key: &CustodyBook,
+    account: &mut CustodyAccount,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+    value: i128,
+) -> Result<(), CustodyError> {
+    check_destination(env, account, to)?;
+
+    let remaining = debit(env, account, token, amount, value)?;
+    save_account(env, account_key, account);
+
+    token::Client::new(env, token).transfer(&env.current_contract_address(), to, &amount);
+
+    publish_vault_event(
+        env,
+        symbol_short!("withdraw"),
+        account,
+        (token.clone(), to.clone(), amount, remaining),
+    );
+
+    log!(
+        env,
+        "✅ Withdrawal of {} to {} successful. Remaining: {}",
+        amount,
+        to.clone(),
+        remaining
+    );
+    Ok(())
+}
+
+/// Fail unless the contract is running and `to` is an active allowlisted
+/// destination of the account.
+fn check_destination(
+    env: &Env,
+    account: &CustodyAccount,
+    to: &Address,
+) -> Result<(), CustodyError> {
+    if env.storage().instance().has(&PAUSED) {
+        return Err(CustodyError::ContractPaused);
+    }
+
+    let active_from = account
+        .allowlist
+        .get(to.clone())
+        .ok_or(CustodyError::DestinationNotAllowed)?;
+    if env.ledger().timestamp() < active_from {
+        return Err(CustodyError::DestinationNotActive);
+    }
+
+    Ok(())
+}
+
+/// Apply a pending withdrawal delay reduction once it has taken effect.
+fn settle_withdrawal_delay(env: &Env, account: &mut CustodyAccount) {
+    if let Some(delay) = account.pending_withdrawal_delay {
+        if env.ledger().timestamp() >= account.withdrawal_delay_effective_at {
+            account.withdrawal_delay = delay;
+            account.pending_withdrawal_delay = None;
+        }
+    }
+}
+
+/// Apply a pending allowlist delay reduction once it has taken effect.
+fn settle_allowlist_delay(env: &Env, account: &mut CustodyAccount) {
+    if let Some(delay) = account.pending_allowlist_delay {
+        if env.ledger().timestamp() >= account.allowlist_delay_effective_at {
+            account.allowlist_delay = delay;
+            account.pending_allowlist_delay = None;
+        }
+    }
+}
+
+/// Amount of the schedule vested at `now`, claimed or not.
+fn vested_amount(schedule: &VestingSchedule, now: u64) -> i128 {
+    if now < schedule.cliff {
+        return 0;
+    }
+    if now >= schedule.end {
+        return schedule.total;
+    }
+
+    let elapsed = (now - schedule.start) as i128;
+    let duration = (schedule.end - schedule.start) as i128;
+    schedule.total * elapsed / duration
+}
+
+/// Take `amount` of `token` off the account's balance, subject to its
+/// velocity limits, and record the outflow as account activity. Returns the
+/// remaining balance; the caller persists the account.
+///
+/// Velocity limits count `value`, the amount in the account's policy unit.
+fn debit(
+    env: &Env,
+    account: &mut CustodyAccount,
+    token: &Address,
+    amount: i128,
+    value: i128,
+) -> Result<i128, CustodyError> {
+    let balance = balance_of(account, token);
+    if balance < amount {
+        return Err(CustodyError::InsufficientBalance);
+    }
+
+    record_spend(env, account, value)?;
+
+    let remaining = balance - amount;
+    account.balances.set(token.clone(), remaining);
+    account.last_activity = env.ledger().timestamp();
+    Ok(remaining)
+}
+
+/// Enforce the account's velocity limits for a new outflow of `amount` and
+/// append it to the spend log, dropping records no limit can see anymore.
+fn record_spend(env: &Env, account: &mut CustodyAccount, amount: i128) -> Result<(), CustodyError> {
+    let now = env.ledger().timestamp();
+
+    let mut longest_period = 0;
+    for limit in account.velocity_limits.iter() {
+        let mut spent = amount;
+        for record in account.spend_log.iter() {
+            if now.saturating_sub(record.timestamp) < limit.period {
+                spent += record.amount;
+            }
+        }
+
+        if spent > limit.cap {
+            return Err(CustodyError::VelocityLimitExceeded);
+        }
+
+        longest_period = longest_period.max(limit.period);
+    }
+
+    let mut spend_log = Vec::new(env);
+    for record in account.spend_log.iter() {
+        if now.saturating_sub(record.timestamp) < longest_period {
+            spend_log.push_back(record);
+        }
+    }
+    if longest_period > 0 {
+        spend_log.push_back(SpendRecord {
+            timestamp: now,
+            amount,
+        });
+    }
+    account.spend_log = spend_log;
+
+    Ok(())
+}
+
+// ----------------------------------------------------------
+// 📈 Price Oracle
+// ----------------------------------------------------------
+
+/// The part of the SEP-40 price feed interface this contract calls.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Most recent price of `asset`, if the feed has one.
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+/// Express `amount` of `token` in the account's policy unit: unchanged
+/// without a price oracle, otherwise its USD value at the oracle's latest
+/// price.
+fn policy_value(
+    env: &Env,
+    account: &CustodyAccount,
+    token: &Address,
+    amount: i128,
+) -> Result<i128, OracleError> {
+    let oracle = match &account.price_oracle {
+        Some(oracle) => oracle,
+        None => return Ok(amount),
+    };
+
+    let quote = PriceOracleClient::new(env, oracle)
+        .lastprice(&Asset::Stellar(token.clone()))
+        .ok_or(OracleError::PriceUnavailable)?;
+    if quote.price <= 0 {
+        return Err(OracleError::PriceUnavailable);
+    }
+
+    let fresh_until = quote
+        .timestamp
+        .checked_add(account.max_price_age)
+        .ok_or(OracleError::PriceOverflow)?;
+    if env.ledger().timestamp() > fresh_until {
+        return Err(OracleError::PriceStale);
+    }
+
+    let decimals = token::Client::new(env, token).decimals();
+    amount
+        .checked_mul(quote.price)
+        .and_then(|scaled| scaled.checked_div(10i128.checked_pow(decimals)?))
+        .ok_or(OracleError::PriceOverflow)
+}
+
+// ----------------------------------------------------------
+// 📣 Events
+// ----------------------------------------------------------
+
+// First topic of every event published by this contract
+const CUSTODY: Symbol = symbol_short!("custody");
+
+/// Publish a vault event with topics `(custody, action, owner, vault_id)`.
+///
+/// Events emitted so far:
+/// - `created`  → (required_signatures, is_insured)
+/// - `deposit`  → (token, amount, balance)
+/// - `dep_for`  → (from, token, amount, balance)
+/// - `withdraw` → (token, to, amount, balance)
+/// - `transfer` → (to_vault, token, amount, balance, to_balance)
+/// - `proposed` → (proposal_id, proposer, token, to, amount)
+/// - `approved` / `revoked` → (proposal_id, signer, approval_weight)
+/// - `executed` → (proposal_id, token, to, amount)
+/// - `queued`   → (proposal_id, unlock_at)
+/// - `cancelled` → (proposal_id, caller)
+/// - `delay`    → (withdrawal_delay, effective_at)
+/// - `velocity` → velocity_limits
+/// - `oracle`   → (price_oracle, max_price_age)
+/// - `allow`    → (destination, active_from)
+/// - `disallow` → destination
+/// - `allow_dly` → (allowlist_delay, effective_at)
+/// - `sgn_add` → (signer, weight)
+/// - `sgn_rm` → signer
+/// - `threshold` → required_signatures
+/// - `tiers`    → approval_tiers
+/// - `roles`    → (member, roles)
+/// - `guardians` → (guardians, threshold, recovery_period)
+/// - `recovery` → (new_owner, executable_at)
+/// - `rec_cncl` → new_owner
+/// - `recovered` → previous_owner
+/// - `heir`     → (heir, dormancy_period, grace_period)
+/// - `heartbeat` → (caller, last_activity)
+/// - `inherit`  → (heir, executable_at)
+/// - `inherited` → previous_owner
+/// - `esc_lock` → (escrow_id, token, amount, beneficiary, hashlock, expires_at)
+/// - `esc_claim` → (escrow_id, beneficiary, preimage)
+/// - `esc_rfnd` → (escrow_id, token, amount, balance)
+/// - `vesting` → (vesting_id, beneficiary, token, total, cliff, start, end)
+/// - `vest_clm` → (vesting_id, amount, claimed)
+/// - `vest_rvk` → (vesting_id, refunded)
+/// - `coverage` → (token, cap)
+/// - `cov_end`  → token
+/// - `premium`  → (token, premium, paid_until)
+/// - `claim`    → (claim_id, token, amount)
+/// - `claim_pay` → (claim_id, token, amount)
+/// - `claim_rej` → claim_id
+/// - `frozen`   → reason
+/// - `unfrozen` → ()
+fn publish_vault_event<D>(env: &Env, action: Symbol, account: &CustodyAccount, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    env.events()
+        .publish((CUSTODY, action, account.owner.clone(), account.id), data);
+}
+
+/// Publish a contract-wide admin event with topics `(custody, action, subject)`,
+/// where the subject is the contract's own address:
+/// - `init`     → (admins, threshold)
+/// - `upgraded` → new_wasm_hash
+/// - `migrated` → (from_version, to_version)
+/// - `paused`   → reason
+/// - `unpaused` → ()
+/// - `ins_cfg`  → (threshold, premium_bps, period)
+/// - `ttl_cfg`  → ttl_config
+fn publish_event<D>(env: &Env, action: Symbol, subject: &Address, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    env.events()
+        .publish((CUSTODY, action, subject.clone()), data);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,1803 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    token::{StellarAssetClient, TokenClient},
+    xdr::{ScErrorCode, ScErrorType},
+};
+
+/// A registered custody contract, a Stellar Asset Contract and a vault
+/// owner holding some of the asset, with three signers and one allowlisted
+/// destination to withdraw to. The first signer is the sole contract admin.
+struct Setup {
+    env: Env,
+    contract: Address,
+    token: Address,
+    owner: Address,
+    signers: Vec<Address>,
+    dest: Address,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let issuer = Address::generate(&env);
+        let token = env.register_stellar_asset_contract_v2(issuer).address();
+        let owner = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&owner, &1_000_000);
+
+        let signers = vec![
+            &env,
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+        let admins = signers.slice(0..1);
+        let contract = env.register(AssetCustodyContract, (admins, 1u32));
+        let dest = Address::generate(&env);
+
+        Setup {
+            env,
+            contract,
+            token,
+            owner,
+            signers,
+            dest,
+        }
+    }
+
+    fn client(&self) -> AssetCustodyContractClient<'_> {
+        AssetCustodyContractClient::new(&self.env, &self.contract)
+    }
+
+    /// Signer weights of 1 each for `signers`.
+    fn weights(&self, signers: &Vec<Address>) -> Map<Address, u32> {
+        let mut weights = Map::new(&self.env);
+        for signer in signers.iter() {
+            weights.set(signer, 1);
+        }
+        weights
+    }
+
+    /// The first `n` signers.
+    fn quorum(&self, n: u32) -> Vec<Address> {
+        self.signers.slice(0..n)
+    }
+
+    /// A 2-of-3 vault with `dest` allowlisted.
+    fn vault(&self) -> u64 {
+        let client = self.client();
+        let vault_id = client.create_custody_account(
+            &self.owner,
+            &self.weights(&self.signers),
+            &2,
+            &0,
+            &false,
+        );
+        client.add_allowed_destination(&vault_id, &self.owner, &self.dest, &self.quorum(2));
+        vault_id
+    }
+
+    /// Remaining lifetime of a persistent contract entry, in ledgers.
+    fn ttl(&self, key: &CustodyBook) -> u32 {
+        self.env.as_contract(&self.contract, || {
+            self.env.storage().persistent().get_ttl(key)
+        })
+    }
+
+    fn token_balance(&self, who: &Address) -> i128 {
+        TokenClient::new(&self.env, &self.token).balance(who)
+    }
+}
+
+/// The contract error a failed `try_` call returned, whichever error enum
+/// the entry point uses.
+fn fails<T, C, E, I>(result: Result<Result<T, C>, Result<E, I>>) -> Error
+where
+    E: Into<Error>,
+    I: core::fmt::Debug,
+{
+    match result {
+        Err(Ok(error)) => error.into(),
+        Err(Err(error)) => panic!("invocation failed without a contract error: {error:?}"),
+        Ok(_) => panic!("call succeeded"),
+    }
+}
+
+#[test]
+fn deposit_and_withdraw_move_tokens() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+
+    client.deposit_assets(&vault_id, &s.token, &400);
+    assert_eq!(s.token_balance(&s.owner), 999_600);
+    assert_eq!(s.token_balance(&s.contract), 400);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 400);
+
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &150, &s.quorum(2));
+    assert_eq!(s.token_balance(&s.dest), 150);
+    assert_eq!(s.token_balance(&s.contract), 250);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 250);
+}
+
+#[test]
+fn withdraw_rejects_more_than_the_balance() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &100);
+
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &101, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::InsufficientBalance.into());
+    assert_eq!(s.token_balance(&s.contract), 100);
+    assert_eq!(s.token_balance(&s.dest), 0);
+}
+
+#[test]
+fn withdraw_requires_auth_from_a_signer_quorum() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &100);
+
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    let authorized: std::vec::Vec<Address> = s.env.auths().into_iter().map(|a| a.0).collect();
+    assert!(authorized.contains(&s.owner));
+    for signer in s.quorum(2).iter() {
+        assert!(authorized.contains(&signer));
+    }
+
+    let one = s.quorum(1);
+    let mut repeated = one.clone();
+    repeated.append(&one);
+    let mut outsider = one.clone();
+    outsider.push_back(s.dest.clone());
+    for (signers, error) in [
+        (one, CustodyError::InsufficientSignatures),
+        (repeated, CustodyError::DuplicateSigner),
+        (outsider, CustodyError::NotASigner),
+    ] {
+        let result =
+            client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &signers);
+        assert_eq!(fails(result), error.into());
+    }
+
+    // Naming a quorum is not enough: its members must actually sign.
+    let signers = s.quorum(2);
+    let invoke = MockAuthInvoke {
+        contract: &s.contract,
+        fn_name: "withdraw_assets",
+        args: (
+            vault_id,
+            s.owner.clone(),
+            s.token.clone(),
+            s.dest.clone(),
+            10i128,
+            signers.clone(),
+        )
+            .into_val(&s.env),
+        sub_invokes: &[],
+    };
+    s.env.mock_auths(&[MockAuth {
+        address: &s.owner,
+        invoke: &invoke,
+    }]);
+    let result = client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &signers);
+    // The host reports a failed `require_auth` to the caller as a generic
+    // context error.
+    assert_eq!(
+        fails(result),
+        Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)
+    );
+    assert_eq!(s.token_balance(&s.contract), 90);
+
+    let (s1, s2) = (signers.get(0).unwrap(), signers.get(1).unwrap());
+    s.env.mock_auths(&[
+        MockAuth {
+            address: &s.owner,
+            invoke: &invoke,
+        },
+        MockAuth {
+            address: &s1,
+            invoke: &invoke,
+        },
+        MockAuth {
+            address: &s2,
+            invoke: &invoke,
+        },
+    ]);
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &signers);
+    assert_eq!(s.token_balance(&s.contract), 80);
+}
+
+#[test]
+fn proposal_lifecycle() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &400);
+    let (s1, s2) = (s.signers.get(0).unwrap(), s.signers.get(1).unwrap());
+
+    let id = client.propose_withdrawal(&vault_id, &s1, &s.token, &s.dest, &100);
+    client.approve_withdrawal(&id, &s1);
+    assert_eq!(
+        fails(client.try_approve_withdrawal(&id, &s1)),
+        CustodyError::AlreadyApproved.into()
+    );
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::InsufficientApprovals.into()
+    );
+
+    client.approve_withdrawal(&id, &s2);
+    client.revoke_approval(&id, &s2);
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::InsufficientApprovals.into()
+    );
+
+    client.approve_withdrawal(&id, &s2);
+    client.execute_withdrawal(&id);
+    assert_eq!(s.token_balance(&s.dest), 100);
+    assert_eq!(
+        client.view_proposal(&id, &s.owner).status,
+        ProposalStatus::Executed
+    );
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::ProposalNotPending.into()
+    );
+}
+
+#[test]
+fn failures_return_typed_error_codes() {
+    let s = Setup::new();
+    let client = s.client();
+
+    assert_eq!(
+        fails(client.try_view_custody_account(&0, &s.owner)),
+        CustodyError::AccountNotFound.into()
+    );
+    assert_eq!(
+        fails(client.try_create_custody_account(&s.owner, &s.weights(&s.signers), &1, &0, &false)),
+        CustodyError::ThresholdTooLow.into()
+    );
+    assert_eq!(
+        fails(client.try_create_custody_account(
+            &s.owner,
+            &s.weights(&s.quorum(1)),
+            &2,
+            &0,
+            &false
+        )),
+        CustodyError::NotEnoughSigners.into()
+    );
+
+    let vault_id = s.vault();
+    assert_eq!(
+        fails(client.try_deposit_assets(&vault_id, &s.token, &0)),
+        CustodyError::InvalidAmount.into()
+    );
+    assert_eq!(
+        fails(client.try_deposit_assets(&vault_id, &s.token, &0)),
+        Error::from_contract_error(4)
+    );
+}
+
+#[test]
+fn deposits_and_withdrawals_publish_events() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+
+    client.deposit_assets(&vault_id, &s.token, &400);
+    let (contract, topics, data) = s.env.events().all().last().unwrap();
+    assert_eq!(contract, s.contract);
+    assert_eq!(
+        topics,
+        (CUSTODY, symbol_short!("deposit"), s.owner.clone(), vault_id).into_val(&s.env)
+    );
+    let data: (Address, i128, i128) = data.into_val(&s.env);
+    assert_eq!(data, (s.token.clone(), 400, 400));
+
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &150, &s.quorum(2));
+    let (_, topics, data) = s.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (
+            CUSTODY,
+            symbol_short!("withdraw"),
+            s.owner.clone(),
+            vault_id
+        )
+            .into_val(&s.env)
+    );
+    let data: (Address, Address, i128, i128) = data.into_val(&s.env);
+    assert_eq!(data, (s.token.clone(), s.dest.clone(), 150, 250));
+}
+
+#[test]
+fn writes_use_the_configured_ttl() {
+    let s = Setup::new();
+    let client = s.client();
+    let admins = s.quorum(1);
+
+    let vault_id = s.vault();
+    let account_key = CustodyBook::Account(vault_id);
+    assert_eq!(s.ttl(&account_key), PERSISTENT_TTL_EXTEND_TO);
+
+    let mut config = client.view_ttl_config();
+    config.persistent_threshold = config.persistent_extend_to + 1;
+    assert_eq!(
+        fails(client.try_set_ttl_config(&config, &admins)),
+        CustodyError::InvalidTtl.into()
+    );
+
+    // Once the TTL has dropped below the new threshold, the next write
+    // extends it to the new target.
+    config.persistent_threshold = PERSISTENT_TTL_EXTEND_TO;
+    config.persistent_extend_to = 2 * PERSISTENT_TTL_EXTEND_TO;
+    client.set_ttl_config(&config, &admins);
+    s.env.ledger().with_mut(|l| l.sequence_number += 10);
+    client.deposit_assets(&vault_id, &s.token, &1);
+    assert_eq!(s.ttl(&account_key), 2 * PERSISTENT_TTL_EXTEND_TO);
+}
+
+#[test]
+fn keepers_extend_a_vault_and_its_entries() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &100);
+    let proposal_id = client.propose_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &10);
+
+    let extend_to = 3 * PERSISTENT_TTL_EXTEND_TO;
+    client.extend_account_ttl(&vault_id, &extend_to);
+    assert_eq!(s.ttl(&CustodyBook::Account(vault_id)), extend_to);
+    assert_eq!(s.ttl(&CustodyBook::OwnerVaults(s.owner.clone())), extend_to);
+    assert_eq!(
+        s.ttl(&CustodyBook::Proposal(proposal_id)),
+        PERSISTENT_TTL_EXTEND_TO
+    );
+
+    let keys = vec![
+        &s.env,
+        CustodyBook::Proposal(proposal_id),
+        CustodyBook::Escrow(7),
+    ];
+    client.extend_entry_ttls(&keys, &extend_to);
+    assert_eq!(s.ttl(&CustodyBook::Proposal(proposal_id)), extend_to);
+
+    assert_eq!(
+        fails(client.try_extend_account_ttl(&9, &extend_to)),
+        CustodyError::AccountNotFound.into()
+    );
+    assert_eq!(
+        fails(client.try_extend_entry_ttls(&keys, &0)),
+        CustodyError::InvalidTtl.into()
+    );
+}
+
+#[test]
+fn balances_are_tracked_per_token() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+
+    let other = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    StellarAssetClient::new(&s.env, &other).mint(&s.owner, &500);
+
+    client.deposit_assets(&vault_id, &s.token, &400);
+    client.deposit_assets(&vault_id, &other, &300);
+    client.withdraw_assets(&vault_id, &s.owner, &other, &s.dest, &100, &s.quorum(2));
+
+    let balances = client.view_custody_account(&vault_id, &s.owner).balances;
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances.get(s.token.clone()), Some(400));
+    assert_eq!(balances.get(other.clone()), Some(200));
+    assert_eq!(TokenClient::new(&s.env, &other).balance(&s.dest), 100);
+    assert_eq!(s.token_balance(&s.dest), 0);
+
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &other, &s.dest, &201, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::InsufficientBalance.into());
+}
+
+#[test]
+fn delayed_withdrawals_unlock_after_the_window() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &400);
+    client.set_withdrawal_delay(&vault_id, &s.owner, &3_600, &s.quorum(2));
+
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::WithdrawalDelayActive.into());
+
+    let now = s.env.ledger().timestamp();
+    let id = client.queue_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(client.view_proposal(&id, &s.owner).queued_at, Some(now));
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::WithdrawalLocked.into()
+    );
+
+    s.env.ledger().with_mut(|l| l.timestamp = now + 3_600);
+    client.execute_withdrawal(&id);
+    assert_eq!(s.token_balance(&s.dest), 100);
+
+    // A queued withdrawal can be stopped during its window.
+    let id = client.queue_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    client.cancel_withdrawal(&id, &s.signers.get(2).unwrap());
+    assert_eq!(
+        client.view_proposal(&id, &s.owner).status,
+        ProposalStatus::Cancelled
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 3_600);
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::ProposalNotPending.into()
+    );
+    assert_eq!(
+        fails(client.try_cancel_withdrawal(&id, &s.dest)),
+        CustodyError::ProposalNotPending.into()
+    );
+    assert_eq!(s.token_balance(&s.contract), 300);
+}
+
+#[test]
+fn withdrawal_delay_reductions_wait_out_the_current_delay() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &400);
+    client.set_withdrawal_delay(&vault_id, &s.owner, &3_600, &s.quorum(2));
+
+    // The quorum cannot drop the delay and withdraw in the same breath.
+    let start = s.env.ledger().timestamp();
+    client.set_withdrawal_delay(&vault_id, &s.owner, &0, &s.quorum(2));
+    let (_, _, data) = s.env.events().all().last().unwrap();
+    let data: (u64, u64) = data.into_val(&s.env);
+    assert_eq!(data, (0, start + 3_600));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::WithdrawalDelayActive.into());
+
+    s.env.ledger().with_mut(|l| l.timestamp = start + 3_600);
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(
+        client
+            .view_custody_account(&vault_id, &s.owner)
+            .withdrawal_delay,
+        0
+    );
+
+    // Increases apply at once.
+    client.set_withdrawal_delay(&vault_id, &s.owner, &60, &s.quorum(2));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::WithdrawalDelayActive.into());
+}
+
+#[test]
+fn velocity_limits_cap_rolling_outflows() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &1_000);
+
+    let day = VelocityLimit {
+        period: 86_400,
+        cap: 150,
+    };
+    let limits = vec![&s.env, day.clone()];
+    assert_eq!(
+        fails(client.try_set_velocity_limits(&vault_id, &s.owner, &limits, &s.quorum(1))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    let invalid = vec![
+        &s.env,
+        VelocityLimit {
+            period: 0,
+            cap: 150,
+        },
+    ];
+    assert_eq!(
+        fails(client.try_set_velocity_limits(&vault_id, &s.owner, &invalid, &s.quorum(2))),
+        CustodyError::InvalidVelocityLimit.into()
+    );
+    client.set_velocity_limits(&vault_id, &s.owner, &limits, &s.quorum(2));
+
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &60, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::VelocityLimitExceeded.into());
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &50, &s.quorum(2));
+
+    // The first withdrawal drops out of the window a day later.
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(s.token_balance(&s.dest), 250);
+}
+
+#[test]
+fn loosening_velocity_limits_needs_the_highest_threshold() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let tiers = vec![
+        &s.env,
+        ApprovalTier {
+            min_amount: 1_000,
+            threshold: 3,
+            delay: 0,
+        },
+    ];
+    client.set_approval_tiers(&vault_id, &s.owner, &tiers, &s.quorum(2));
+    let limit = |cap| VelocityLimit {
+        period: 86_400,
+        cap,
+    };
+    client.set_velocity_limits(&vault_id, &s.owner, &vec![&s.env, limit(150)], &s.quorum(2));
+
+    // Tightening only needs the base quorum.
+    let tighter = vec![&s.env, limit(100), limit(50)];
+    client.set_velocity_limits(&vault_id, &s.owner, &tighter, &s.quorum(2));
+
+    for looser in [vec![&s.env, limit(1_000)], Vec::new(&s.env)] {
+        let result = client.try_set_velocity_limits(&vault_id, &s.owner, &looser, &s.quorum(2));
+        assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    }
+    client.set_velocity_limits(&vault_id, &s.owner, &Vec::new(&s.env), &s.quorum(3));
+    assert!(client
+        .view_custody_account(&vault_id, &s.owner)
+        .velocity_limits
+        .is_empty());
+}
+
+#[test]
+fn allowlist_delay_reductions_wait_out_the_current_delay() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &400);
+    client.set_allowlist_delay(&vault_id, &s.owner, &86_400, &s.quorum(2));
+
+    let start = s.env.ledger().timestamp();
+    let late = Address::generate(&s.env);
+    client.add_allowed_destination(&vault_id, &s.owner, &late, &s.quorum(2));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &late, &10, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::DestinationNotActive.into());
+
+    // Dropping the delay does not let a new destination in any sooner.
+    client.set_allowlist_delay(&vault_id, &s.owner, &0, &s.quorum(2));
+    let (_, _, data) = s.env.events().all().last().unwrap();
+    let data: (u64, u64) = data.into_val(&s.env);
+    assert_eq!(data, (0, start + 86_400));
+
+    let sooner = Address::generate(&s.env);
+    client.add_allowed_destination(&vault_id, &s.owner, &sooner, &s.quorum(2));
+    let account = client.view_custody_account(&vault_id, &s.owner);
+    assert_eq!(account.allowlist.get(sooner.clone()), Some(start + 86_400));
+    assert_eq!(account.pending_allowlist_delay, Some(0));
+
+    // Once the old delay has passed, the reduction applies.
+    s.env.ledger().with_mut(|l| l.timestamp = start + 86_400);
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &late, &10, &s.quorum(2));
+    let fresh = Address::generate(&s.env);
+    client.add_allowed_destination(&vault_id, &s.owner, &fresh, &s.quorum(2));
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &fresh, &10, &s.quorum(2));
+    assert_eq!(
+        client
+            .view_custody_account(&vault_id, &s.owner)
+            .allowlist_delay,
+        0
+    );
+}
+
+#[test]
+fn the_constructor_sets_the_admin_set() {
+    let s = Setup::new();
+    let config = s.client().view_admins();
+    assert_eq!(config.admins, s.quorum(1));
+    assert_eq!(config.threshold, 1);
+    assert_eq!(s.client().schema_version(), CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn upgrades_and_migrations_need_an_admin_quorum() {
+    let s = Setup::new();
+    let client = s.client();
+    let outsiders = vec![&s.env, s.dest.clone()];
+
+    let hash = BytesN::from_array(&s.env, &[7; 32]);
+    assert_eq!(
+        fails(client.try_upgrade(&hash, &outsiders)),
+        CustodyError::NotAnAdmin.into()
+    );
+    assert_eq!(
+        fails(client.try_migrate(&outsiders)),
+        CustodyError::NotAnAdmin.into()
+    );
+
+    assert_eq!(client.migrate(&s.quorum(1)), CURRENT_SCHEMA_VERSION);
+    assert_eq!(client.schema_version(), CURRENT_SCHEMA_VERSION);
+
+    // Code older than the stored layout must not touch it.
+    s.env.as_contract(&s.contract, || {
+        s.env
+            .storage()
+            .instance()
+            .set(&SCHEMA_VERSION, &(CURRENT_SCHEMA_VERSION + 1));
+    });
+    assert_eq!(
+        fails(client.try_migrate(&s.quorum(1))),
+        CustodyError::UnsupportedSchemaVersion.into()
+    );
+    assert_eq!(client.schema_version(), CURRENT_SCHEMA_VERSION + 1);
+}
+
+#[test]
+#[should_panic]
+fn the_constructor_rejects_an_unreachable_threshold() {
+    let env = Env::default();
+    let admins = vec![&env, Address::generate(&env)];
+    env.register(AssetCustodyContract, (admins, 2u32));
+}
+
+#[test]
+fn pause_stops_outflows_and_freeze_stops_an_account() {
+    let s = Setup::new();
+    let client = s.client();
+    let admins = s.quorum(1);
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &400);
+
+    assert_eq!(
+        fails(client.try_pause(&7, &vec![&s.env, s.dest.clone()])),
+        CustodyError::NotAnAdmin.into()
+    );
+    client.pause(&7, &admins);
+    assert_eq!(client.paused(), Some(7));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::ContractPaused.into());
+
+    client.unpause(&admins);
+    assert_eq!(client.paused(), None);
+    assert_eq!(
+        fails(client.try_unpause(&admins)),
+        CustodyError::ContractNotPaused.into()
+    );
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+
+    client.freeze_account(&vault_id, &3, &admins);
+    let (_, topics, data) = s.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (CUSTODY, symbol_short!("frozen"), s.owner.clone(), vault_id).into_val(&s.env)
+    );
+    let reason: u32 = data.into_val(&s.env);
+    assert_eq!(reason, 3);
+    assert_eq!(
+        fails(client.try_deposit_assets(&vault_id, &s.token, &10)),
+        CustodyError::AccountInactive.into()
+    );
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::AccountInactive.into());
+
+    client.unfreeze_account(&vault_id, &admins);
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(s.token_balance(&s.dest), 20);
+}
+
+#[test]
+fn insurance_premiums_fund_committee_approved_claims() {
+    let s = Setup::new();
+    let client = s.client();
+    let committee = vec![&s.env, Address::generate(&s.env)];
+    client.configure_insurance(&committee, &1, &100, &86_400, &s.quorum(1));
+
+    let vault_id = client.create_custody_account(&s.owner, &s.weights(&s.signers), &2, &0, &true);
+    client.deposit_assets(&vault_id, &s.token, &1_000);
+
+    // The account's quorum has to agree to the cap it will pay premiums on.
+    let signer = s.signers.get(0).unwrap();
+    let result = client.try_set_coverage(
+        &vault_id,
+        &signer,
+        &s.token,
+        &5_000,
+        &s.quorum(2),
+        &committee,
+    );
+    assert_eq!(fails(result), CustodyError::MissingRole.into());
+    let result = client.try_set_coverage(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &5_000,
+        &s.quorum(1),
+        &committee,
+    );
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    let result = client.try_set_coverage(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &5_000,
+        &s.quorum(2),
+        &s.quorum(1),
+    );
+    assert_eq!(fails(result), CustodyError::NotOnCommittee.into());
+    client.set_coverage(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &5_000,
+        &s.quorum(2),
+        &committee,
+    );
+    let evidence = BytesN::from_array(&s.env, &[1; 32]);
+    assert_eq!(
+        fails(client.try_file_claim(&vault_id, &s.token, &100, &evidence)),
+        CustodyError::CoverageInactive.into()
+    );
+
+    assert_eq!(client.collect_premium(&vault_id, &s.token), 50);
+    assert_eq!(
+        fails(client.try_collect_premium(&vault_id, &s.token)),
+        CustodyError::PremiumNotDue.into()
+    );
+    assert_eq!(client.view_insurance_pool(&s.token), 50);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 950);
+
+    let id = client.file_claim(&vault_id, &s.token, &80, &evidence);
+    assert_eq!(
+        fails(client.try_approve_claim(&id, &committee)),
+        CustodyError::InsufficientPool.into()
+    );
+    client.reject_claim(&id, &committee);
+    assert_eq!(client.view_claim(&id).status, ClaimStatus::Rejected);
+
+    let id = client.file_claim(&vault_id, &s.token, &40, &evidence);
+    client.approve_claim(&id, &committee);
+    assert_eq!(client.view_claim(&id).status, ClaimStatus::Paid);
+    assert_eq!(client.view_insurance_pool(&s.token), 10);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 990);
+    assert_eq!(
+        fails(client.try_file_claim(&vault_id, &s.token, &4_961, &evidence)),
+        CustodyError::CoverageExceeded.into()
+    );
+
+    // The vault can walk away from coverage on its own.
+    client.end_coverage(&vault_id, &s.owner, &s.token, &s.quorum(2));
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(
+        fails(client.try_collect_premium(&vault_id, &s.token)),
+        CustodyError::NotInsured.into()
+    );
+    assert_eq!(
+        fails(client.try_end_coverage(&vault_id, &s.owner, &s.token, &s.quorum(2))),
+        CustodyError::NotInsured.into()
+    );
+}
+
+#[test]
+fn guardians_recover_a_vault_after_the_challenge_period() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let guardians = vec![&s.env, Address::generate(&s.env), Address::generate(&s.env)];
+
+    // Without a challenge period the owner could never cancel a recovery.
+    let result = client.try_set_guardians(&vault_id, &s.owner, &guardians, &2, &0, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::InvalidGuardianConfig.into());
+    client.set_guardians(&vault_id, &s.owner, &Vec::new(&s.env), &0, &0, &s.quorum(2));
+    client.set_guardians(&vault_id, &s.owner, &guardians, &2, &3_600, &s.quorum(2));
+
+    let new_owner = Address::generate(&s.env);
+    assert_eq!(
+        fails(client.try_start_recovery(&vault_id, &new_owner, &guardians.slice(0..1))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    client.start_recovery(&vault_id, &new_owner, &guardians);
+    client.cancel_recovery(&vault_id);
+    assert_eq!(
+        fails(client.try_view_recovery(&vault_id)),
+        CustodyError::RecoveryNotFound.into()
+    );
+
+    let executable_at = client.start_recovery(&vault_id, &new_owner, &guardians);
+    assert_eq!(
+        fails(client.try_finalize_recovery(&vault_id)),
+        CustodyError::RecoveryLocked.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp = executable_at);
+    client.finalize_recovery(&vault_id);
+
+    assert_eq!(
+        client.view_custody_account(&vault_id, &new_owner).owner,
+        new_owner
+    );
+    assert_eq!(client.list_vaults(&new_owner), vec![&s.env, vault_id]);
+    assert_eq!(client.list_vaults(&s.owner), Vec::new(&s.env));
+}
+
+#[test]
+fn signers_and_threshold_change_through_the_quorum() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let newcomer = Address::generate(&s.env);
+
+    assert_eq!(
+        fails(client.try_add_signer(&vault_id, &s.owner, &newcomer, &1, &s.quorum(1))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    client.add_signer(&vault_id, &s.owner, &newcomer, &1, &s.quorum(2));
+    assert_eq!(
+        fails(client.try_add_signer(&vault_id, &s.owner, &newcomer, &1, &s.quorum(2))),
+        CustodyError::DuplicateSigner.into()
+    );
+
+    assert_eq!(
+        fails(client.try_set_threshold(&vault_id, &s.owner, &1, &s.quorum(2))),
+        CustodyError::ThresholdTooLow.into()
+    );
+    assert_eq!(
+        fails(client.try_set_threshold(&vault_id, &s.owner, &5, &s.quorum(2))),
+        CustodyError::NotEnoughSigners.into()
+    );
+    client.set_threshold(&vault_id, &s.owner, &4, &s.quorum(2));
+
+    // The raised threshold now governs signer changes too.
+    let leaver = s.signers.get(2).unwrap();
+    assert_eq!(
+        fails(client.try_remove_signer(&vault_id, &s.owner, &leaver, &s.quorum(3))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    let mut everyone = s.quorum(3);
+    everyone.push_back(newcomer.clone());
+    assert_eq!(
+        fails(client.try_remove_signer(&vault_id, &s.owner, &leaver, &everyone)),
+        CustodyError::NotEnoughSigners.into()
+    );
+    client.set_threshold(&vault_id, &s.owner, &3, &everyone);
+    client.deposit_assets(&vault_id, &s.token, &100);
+    let id = client.propose_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &10);
+    client.remove_signer(&vault_id, &s.owner, &leaver, &everyone);
+
+    let account = client.view_custody_account(&vault_id, &s.owner);
+    assert_eq!(account.required_signatures, 3);
+    assert_eq!(account.signers.len(), 3);
+    assert!(account.signers.contains_key(newcomer));
+    assert!(!account.signers.contains_key(leaver.clone()));
+
+    // The removed signer keeps none of its roles.
+    assert!(!account.roles.contains_key(leaver.clone()));
+    assert_eq!(
+        fails(client.try_cancel_withdrawal(&id, &leaver)),
+        CustodyError::NotAuthorizedToCancel.into()
+    );
+    let result = client.try_propose_withdrawal(&vault_id, &leaver, &s.token, &s.dest, &10);
+    assert_eq!(fails(result), CustodyError::NotAuthorizedToPropose.into());
+}
+
+#[test]
+fn an_owner_holds_several_vaults() {
+    let s = Setup::new();
+    let client = s.client();
+    let operating = s.vault();
+    let reserve = s.vault();
+    assert_ne!(operating, reserve);
+    assert_eq!(
+        client.list_vaults(&s.owner),
+        vec![&s.env, operating, reserve]
+    );
+    assert_eq!(client.total_accounts(), 2);
+
+    client.deposit_assets(&operating, &s.token, &300);
+    client.deposit_assets(&reserve, &s.token, &700);
+    client.withdraw_assets(&reserve, &s.owner, &s.token, &s.dest, &200, &s.quorum(2));
+    assert_eq!(client.view_balance(&operating, &s.token, &s.owner), 300);
+    assert_eq!(client.view_balance(&reserve, &s.token, &s.owner), 500);
+
+    let stranger = Address::generate(&s.env);
+    assert_eq!(client.list_vaults(&stranger), Vec::new(&s.env));
+    assert_eq!(
+        fails(client.try_view_custody_account(&reserve, &stranger)),
+        CustodyError::MissingRole.into()
+    );
+}
+
+#[test]
+fn signer_weights_count_toward_the_threshold() {
+    let s = Setup::new();
+    let client = s.client();
+    let (cfo, operator) = (s.signers.get(0).unwrap(), s.signers.get(1).unwrap());
+
+    let mut weights = s.weights(&s.signers);
+    weights.set(cfo.clone(), 0);
+    assert_eq!(
+        fails(client.try_create_custody_account(&s.owner, &weights, &2, &0, &false)),
+        CustodyError::InvalidSignerWeight.into()
+    );
+
+    // No single key may reach the threshold on its own.
+    weights.set(cfo.clone(), 3);
+    assert_eq!(
+        fails(client.try_create_custody_account(&s.owner, &weights, &3, &0, &false)),
+        CustodyError::InvalidSignerWeight.into()
+    );
+
+    weights.set(cfo.clone(), 2);
+    let vault_id = client.create_custody_account(&s.owner, &weights, &3, &0, &false);
+    let quorum = vec![&s.env, cfo.clone(), operator.clone()];
+    let newcomer = Address::generate(&s.env);
+    assert_eq!(
+        fails(client.try_add_signer(&vault_id, &s.owner, &newcomer, &3, &quorum)),
+        CustodyError::InvalidSignerWeight.into()
+    );
+    assert_eq!(
+        fails(client.try_set_threshold(&vault_id, &s.owner, &2, &quorum)),
+        CustodyError::ThresholdTooLow.into()
+    );
+    client.add_allowed_destination(
+        &vault_id,
+        &s.owner,
+        &s.dest,
+        &vec![&s.env, cfo.clone(), operator.clone()],
+    );
+    client.deposit_assets(&vault_id, &s.token, &100);
+
+    // The CFO counts double, but still needs an operator to reach 3.
+    let cfo_only = vec![&s.env, cfo.clone()];
+    let result = client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &cfo_only);
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    let operators = s.signers.slice(1..3);
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &operators);
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+
+    client.withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &10,
+        &vec![&s.env, cfo, operator],
+    );
+    assert_eq!(s.token_balance(&s.dest), 10);
+}
+
+#[test]
+fn roles_separate_proposing_approving_and_auditing() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &100);
+
+    let approver = s.signers.get(0).unwrap();
+    let auditor = Address::generate(&s.env);
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &approver,
+        &vec![&s.env, Role::Approver],
+        &s.quorum(2),
+    );
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &auditor,
+        &vec![&s.env, Role::Auditor],
+        &s.quorum(2),
+    );
+
+    // Approvers cannot start withdrawals.
+    assert_eq!(
+        fails(client.try_propose_withdrawal(&vault_id, &approver, &s.token, &s.dest, &10)),
+        CustodyError::NotAuthorizedToPropose.into()
+    );
+    let result =
+        client.try_withdraw_assets(&vault_id, &approver, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::MissingRole.into());
+
+    // Auditors can look but not act.
+    assert_eq!(client.view_balance(&vault_id, &s.token, &auditor), 100);
+    let id = client.propose_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &10);
+    assert_eq!(client.view_proposal(&id, &auditor).amount, 10);
+    assert_eq!(
+        fails(client.try_approve_withdrawal(&id, &auditor)),
+        CustodyError::NotASigner.into()
+    );
+    let signer = s.signers.get(2).unwrap();
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &signer,
+        &vec![&s.env, Role::Auditor],
+        &s.quorum(2),
+    );
+    assert_eq!(
+        fails(client.try_approve_withdrawal(&id, &signer)),
+        CustodyError::MissingRole.into()
+    );
+    let result = client.try_set_withdrawal_delay(&vault_id, &auditor, &0, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::MissingRole.into());
+
+    // Revoking every role removes read access too.
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &auditor,
+        &Vec::new(&s.env),
+        &s.quorum(2),
+    );
+    assert_eq!(
+        fails(client.try_view_balance(&vault_id, &s.token, &auditor)),
+        CustodyError::MissingRole.into()
+    );
+}
+
+#[test]
+fn approval_tiers_pick_the_threshold_and_delay_by_amount() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &200_000);
+
+    let tier = |min_amount, threshold, delay| ApprovalTier {
+        min_amount,
+        threshold,
+        delay,
+    };
+    let unreachable = vec![&s.env, tier(1_000, 4, 0)];
+    assert_eq!(
+        fails(client.try_set_approval_tiers(&vault_id, &s.owner, &unreachable, &s.quorum(2))),
+        CustodyError::InvalidApprovalTier.into()
+    );
+    let unordered = vec![&s.env, tier(1_000, 3, 0), tier(1_000, 3, 60)];
+    assert_eq!(
+        fails(client.try_set_approval_tiers(&vault_id, &s.owner, &unordered, &s.quorum(2))),
+        CustodyError::InvalidApprovalTier.into()
+    );
+    let tiers = vec![&s.env, tier(1_000, 3, 0), tier(100_000, 3, 3_600)];
+    client.set_approval_tiers(&vault_id, &s.owner, &tiers, &s.quorum(2));
+
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &999, &s.quorum(2));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1_000, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1_000, &s.quorum(3));
+
+    let result = client.try_withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &100_000,
+        &s.quorum(3),
+    );
+    assert_eq!(fails(result), CustodyError::WithdrawalDelayActive.into());
+    let id = client.queue_withdrawal(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &100_000,
+        &s.quorum(3),
+    );
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::WithdrawalLocked.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 3_600);
+    client.execute_withdrawal(&id);
+}
+
+#[test]
+fn the_base_quorum_cannot_loosen_approval_tiers() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &10_000);
+
+    let tier = |min_amount, threshold| ApprovalTier {
+        min_amount,
+        threshold,
+        delay: 0,
+    };
+    client.set_approval_tiers(
+        &vault_id,
+        &s.owner,
+        &vec![&s.env, tier(1_000, 3)],
+        &s.quorum(2),
+    );
+
+    // Two signers cannot clear the tier that holds them back, nor add a
+    // signer or grant a role to get around it.
+    let none = Vec::new(&s.env);
+    assert_eq!(
+        fails(client.try_set_approval_tiers(&vault_id, &s.owner, &none, &s.quorum(2))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1_000, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    let newcomer = Address::generate(&s.env);
+    assert_eq!(
+        fails(client.try_add_signer(&vault_id, &s.owner, &newcomer, &1, &s.quorum(2))),
+        CustodyError::InsufficientSignatures.into()
+    );
+    let roles = vec![&s.env, Role::Proposer, Role::Approver, Role::Admin];
+    let signer = s.signers.get(0).unwrap();
+    assert_eq!(
+        fails(client.try_set_roles(&vault_id, &s.owner, &signer, &roles, &s.quorum(2))),
+        CustodyError::InsufficientSignatures.into()
+    );
+
+    // Tightening stays with the base quorum; loosening takes all three.
+    let lower = vec![&s.env, tier(500, 3)];
+    client.set_approval_tiers(&vault_id, &s.owner, &lower, &s.quorum(2));
+    client.set_approval_tiers(&vault_id, &s.owner, &none, &s.quorum(3));
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1_000, &s.quorum(2));
+}
+
+#[test]
+fn third_parties_deposit_with_their_own_auth() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let customer = Address::generate(&s.env);
+    StellarAssetClient::new(&s.env, &s.token).mint(&customer, &500);
+
+    client.deposit_for(&customer, &vault_id, &s.token, &200);
+    assert_eq!(
+        s.env
+            .auths()
+            .into_iter()
+            .map(|a| a.0)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![customer.clone()]
+    );
+    assert_eq!(s.token_balance(&customer), 300);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 200);
+
+    let (_, topics, data) = s.env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (CUSTODY, symbol_short!("dep_for"), s.owner.clone(), vault_id).into_val(&s.env)
+    );
+    let data: (Address, Address, i128, i128) = data.into_val(&s.env);
+    assert_eq!(data, (customer.clone(), s.token.clone(), 200, 200));
+
+    assert_eq!(
+        fails(client.try_deposit_for(&customer, &vault_id, &s.token, &0)),
+        CustodyError::InvalidAmount.into()
+    );
+}
+
+#[test]
+fn transfers_stay_between_one_owners_vaults() {
+    let s = Setup::new();
+    let client = s.client();
+    let operating = s.vault();
+    let reserve = s.vault();
+    client.deposit_assets(&operating, &s.token, &500);
+
+    let outsider = Address::generate(&s.env);
+    let foreign = client.create_custody_account(&outsider, &s.weights(&s.signers), &2, &0, &false);
+    let result = client.try_transfer_between_accounts(
+        &operating,
+        &s.owner,
+        &foreign,
+        &s.token,
+        &100,
+        &s.quorum(2),
+    );
+    assert_eq!(fails(result), CustodyError::DestinationNotAllowed.into());
+    let result = client.try_transfer_between_accounts(
+        &operating,
+        &s.owner,
+        &operating,
+        &s.token,
+        &100,
+        &s.quorum(2),
+    );
+    assert_eq!(fails(result), CustodyError::SameVault.into());
+
+    client.transfer_between_accounts(&operating, &s.owner, &reserve, &s.token, &100, &s.quorum(2));
+    assert_eq!(client.view_balance(&operating, &s.token, &s.owner), 400);
+    assert_eq!(client.view_balance(&reserve, &s.token, &s.owner), 100);
+    assert_eq!(s.token_balance(&s.contract), 500);
+}
+
+#[test]
+fn escrows_release_on_the_preimage_or_refund_after_expiry() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &500);
+
+    let preimage = Bytes::from_array(&s.env, b"swap secret");
+    let hashlock: BytesN<32> = s.env.crypto().sha256(&preimage).into();
+    let lock = |amount: &i128| {
+        client.lock_escrow(
+            &vault_id,
+            &s.owner,
+            &s.token,
+            amount,
+            &s.dest,
+            &hashlock,
+            &3_600,
+            &s.quorum(2),
+        )
+    };
+
+    let id = lock(&100);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 400);
+    let wrong = Bytes::from_array(&s.env, b"guess");
+    assert_eq!(
+        fails(client.try_claim_escrow(&id, &wrong)),
+        EscrowError::InvalidPreimage.into()
+    );
+    assert_eq!(
+        fails(client.try_refund_escrow(&id)),
+        EscrowError::EscrowNotExpired.into()
+    );
+    client.claim_escrow(&id, &preimage);
+    assert_eq!(s.token_balance(&s.dest), 100);
+    assert_eq!(client.view_escrow(&id).status, EscrowStatus::Claimed);
+
+    let id = lock(&150);
+    s.env.ledger().with_mut(|l| l.timestamp += 3_600);
+    assert_eq!(
+        fails(client.try_claim_escrow(&id, &preimage)),
+        EscrowError::EscrowExpired.into()
+    );
+    client.refund_escrow(&id);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 400);
+    assert_eq!(
+        fails(client.try_refund_escrow(&id)),
+        EscrowError::EscrowNotLocked.into()
+    );
+    assert_eq!(s.token_balance(&s.contract), 400);
+}
+
+#[test]
+fn vesting_pays_out_linearly_and_revocation_returns_the_rest() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &1_500);
+
+    let start = 1_000;
+    s.env.ledger().with_mut(|l| l.timestamp = start);
+    let result = client.try_create_vesting(
+        &vault_id,
+        &s.owner,
+        &s.dest,
+        &s.token,
+        &1_000,
+        &(start + 2_000),
+        &start,
+        &(start + 1_000),
+        &s.quorum(2),
+    );
+    assert_eq!(fails(result), VestingError::InvalidSchedule.into());
+    let id = client.create_vesting(
+        &vault_id,
+        &s.owner,
+        &s.dest,
+        &s.token,
+        &1_000,
+        &(start + 100),
+        &start,
+        &(start + 1_000),
+        &s.quorum(2),
+    );
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 500);
+
+    s.env.ledger().with_mut(|l| l.timestamp = start + 50);
+    assert_eq!(
+        fails(client.try_claim_vested(&id)),
+        VestingError::NothingToClaim.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp = start + 500);
+    assert_eq!(client.claim_vested(&id), 500);
+
+    s.env.ledger().with_mut(|l| l.timestamp = start + 600);
+    assert_eq!(client.revoke_vesting(&id, &s.owner, &s.quorum(2)), 400);
+    assert_eq!(client.view_balance(&vault_id, &s.token, &s.owner), 900);
+    assert_eq!(
+        fails(client.try_revoke_vesting(&id, &s.owner, &s.quorum(2))),
+        VestingError::VestingRevoked.into()
+    );
+
+    // What vested before the revocation stays claimable.
+    s.env.ledger().with_mut(|l| l.timestamp = start + 1_000);
+    assert_eq!(client.claim_vested(&id), 100);
+    assert_eq!(s.token_balance(&s.dest), 600);
+    assert_eq!(
+        fails(client.try_claim_vested(&id)),
+        VestingError::NothingToClaim.into()
+    );
+}
+
+#[test]
+fn heirs_inherit_dormant_vaults() {
+    let s = Setup::new();
+    let client = s.client();
+    let heir = s.signers.get(0).unwrap();
+
+    // The owner holds a seat of its own, which the heir adds to theirs as
+    // far as one below the threshold.
+    let mut weights = s.weights(&s.signers);
+    weights.set(s.owner.clone(), 1);
+    let vault_id = client.create_custody_account(&s.owner, &weights, &2, &0, &false);
+    client.set_heir(
+        &vault_id,
+        &s.owner,
+        &Some(heir.clone()),
+        &86_400,
+        &3_600,
+        &s.quorum(2),
+    );
+
+    assert_eq!(
+        fails(client.try_start_inheritance(&vault_id)),
+        InheritanceError::AccountNotDormant.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    let executable_at = client.start_inheritance(&vault_id);
+    assert_eq!(
+        fails(client.try_start_inheritance(&vault_id)),
+        InheritanceError::InheritanceInProgress.into()
+    );
+    assert_eq!(
+        fails(client.try_finalize_inheritance(&vault_id)),
+        InheritanceError::InheritanceLocked.into()
+    );
+
+    s.env.ledger().with_mut(|l| l.timestamp = executable_at);
+    client.finalize_inheritance(&vault_id);
+    let account = client.view_custody_account(&vault_id, &heir);
+    assert_eq!(account.owner, heir);
+    assert_eq!(account.heir, None);
+    assert_eq!(account.signers.get(heir.clone()), Some(1));
+    assert!(!account.signers.contains_key(s.owner.clone()));
+    assert_eq!(client.list_vaults(&heir), vec![&s.env, vault_id]);
+}
+
+#[test]
+fn activity_defeats_an_inheritance_claim_without_locking_out_the_heir() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let heir = Address::generate(&s.env);
+    client.set_heir(
+        &vault_id,
+        &s.owner,
+        &Some(heir.clone()),
+        &86_400,
+        &3_600,
+        &s.quorum(2),
+    );
+
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    client.start_inheritance(&vault_id);
+    s.env.ledger().with_mut(|l| l.timestamp += 10);
+    client.deposit_assets(&vault_id, &s.token, &100);
+    s.env.ledger().with_mut(|l| l.timestamp += 3_600);
+    assert_eq!(
+        fails(client.try_finalize_inheritance(&vault_id)),
+        InheritanceError::AccountNotDormant.into()
+    );
+
+    // The defeated claim neither blocks policy changes nor a later claim.
+    client.set_heir(
+        &vault_id,
+        &s.owner,
+        &Some(heir.clone()),
+        &86_400,
+        &3_600,
+        &s.quorum(2),
+    );
+    assert_eq!(
+        fails(client.try_view_inheritance(&vault_id)),
+        InheritanceError::InheritanceNotFound.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    client.start_inheritance(&vault_id);
+    client.deposit_assets(&vault_id, &s.token, &100);
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    let executable_at = client.start_inheritance(&vault_id);
+    s.env.ledger().with_mut(|l| l.timestamp = executable_at);
+    client.finalize_inheritance(&vault_id);
+    assert_eq!(client.view_custody_account(&vault_id, &heir).owner, heir);
+}
+
+#[test]
+fn heirs_need_a_grace_period() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let heir = Some(Address::generate(&s.env));
+
+    let result = client.try_set_heir(&vault_id, &s.owner, &heir, &86_400, &0, &s.quorum(2));
+    assert_eq!(
+        fails(result),
+        InheritanceError::InvalidInheritanceConfig.into()
+    );
+    client.set_heir(&vault_id, &s.owner, &None, &0, &0, &s.quorum(2));
+}
+
+#[test]
+fn a_new_heir_waits_out_a_full_dormancy_period() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let heir = Some(Address::generate(&s.env));
+
+    // The vault has been quiet for far longer than the dormancy period.
+    s.env.ledger().with_mut(|l| l.timestamp += 10 * 86_400);
+    client.set_heir(&vault_id, &s.owner, &heir, &86_400, &3_600, &s.quorum(2));
+    assert_eq!(
+        fails(client.try_start_inheritance(&vault_id)),
+        InheritanceError::AccountNotDormant.into()
+    );
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    client.start_inheritance(&vault_id);
+}
+
+#[test]
+fn only_admins_can_send_a_heartbeat() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    let auditor = Address::generate(&s.env);
+    let admin = s.signers.get(0).unwrap();
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &auditor,
+        &vec![&s.env, Role::Auditor],
+        &s.quorum(2),
+    );
+    client.set_roles(
+        &vault_id,
+        &s.owner,
+        &admin,
+        &vec![&s.env, Role::Approver, Role::Admin],
+        &s.quorum(2),
+    );
+    let heir = Some(Address::generate(&s.env));
+    client.set_heir(&vault_id, &s.owner, &heir, &86_400, &3_600, &s.quorum(2));
+
+    s.env.ledger().with_mut(|l| l.timestamp += 86_400);
+    client.start_inheritance(&vault_id);
+    assert_eq!(
+        fails(client.try_heartbeat(&vault_id, &auditor)),
+        CustodyError::MissingRole.into()
+    );
+    assert_eq!(
+        fails(client.try_heartbeat(&vault_id, &s.signers.get(1).unwrap())),
+        CustodyError::MissingRole.into()
+    );
+    client.heartbeat(&vault_id, &admin);
+    assert_eq!(
+        fails(client.try_view_inheritance(&vault_id)),
+        InheritanceError::InheritanceNotFound.into()
+    );
+    client.heartbeat(&vault_id, &s.owner);
+}
+
+#[test]
+fn far_off_deadlines_fail_cleanly_instead_of_overflowing() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &500);
+    s.env.ledger().with_mut(|l| l.timestamp = 1_000);
+
+    let hashlock = BytesN::from_array(&s.env, &[7; 32]);
+    let result = client.try_lock_escrow(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &100,
+        &s.dest,
+        &hashlock,
+        &u64::MAX,
+        &s.quorum(2),
+    );
+    assert_eq!(fails(result), EscrowError::InvalidTimeout.into());
+
+    // A window reaching past the end of the clock still counts every spend.
+    let limits = vec![
+        &s.env,
+        VelocityLimit {
+            period: u64::MAX,
+            cap: 150,
+        },
+    ];
+    client.set_velocity_limits(&vault_id, &s.owner, &limits, &s.quorum(2));
+    client.withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &100, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::VelocityLimitExceeded.into());
+
+    let heir = Some(Address::generate(&s.env));
+    client.set_heir(&vault_id, &s.owner, &heir, &u64::MAX, &3_600, &s.quorum(2));
+    assert_eq!(
+        fails(client.try_start_inheritance(&vault_id)),
+        InheritanceError::AccountNotDormant.into()
+    );
+
+    let guardians = vec![&s.env, Address::generate(&s.env)];
+    client.set_guardians(&vault_id, &s.owner, &guardians, &1, &u64::MAX, &s.quorum(2));
+    let new_owner = Address::generate(&s.env);
+    assert_eq!(
+        fails(client.try_start_recovery(&vault_id, &new_owner, &guardians)),
+        CustodyError::InvalidGuardianConfig.into()
+    );
+
+    client.set_withdrawal_delay(&vault_id, &s.owner, &u64::MAX, &s.quorum(2));
+    let result =
+        client.try_queue_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), CustodyError::WithdrawalDelayActive.into());
+}
+
+mod oracle {
+    use crate::{Asset, PriceData};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// SEP-40 price feed returning whatever price was last set.
+    #[contract]
+    pub struct MockOracle;
+
+    #[contractimpl]
+    impl MockOracle {
+        pub fn set_price(env: Env, price: i128, timestamp: u64) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("price"), &PriceData { price, timestamp });
+        }
+
+        pub fn lastprice(env: Env, _asset: Asset) -> Option<PriceData> {
+            env.storage().instance().get(&symbol_short!("price"))
+        }
+    }
+}
+
+use oracle::{MockOracle, MockOracleClient};
+
+/// One US dollar at the mock oracle's 14 decimals.
+const USD: i128 = 100_000_000_000_000;
+
+#[test]
+fn oracle_prices_tiers_and_limits_in_usd() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    StellarAssetClient::new(&s.env, &s.token).mint(&s.owner, &1_000_000_000);
+    client.deposit_assets(&vault_id, &s.token, &1_000_000_000);
+
+    let oracle = s.env.register(MockOracle, ());
+    let feed = MockOracleClient::new(&s.env, &oracle);
+    let result =
+        client.try_set_price_oracle(&vault_id, &s.owner, &Some(oracle.clone()), &0, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::InvalidOracleConfig.into());
+    client.set_price_oracle(
+        &vault_id,
+        &s.owner,
+        &Some(oracle.clone()),
+        &300,
+        &s.quorum(2),
+    );
+
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::PriceUnavailable.into());
+
+    // $2 per token; the asset has 7 decimals.
+    s.env.ledger().with_mut(|l| l.timestamp = 1_000);
+    feed.set_price(&(2 * USD), &1_000);
+    let tiers = vec![
+        &s.env,
+        ApprovalTier {
+            min_amount: 20 * USD,
+            threshold: 3,
+            delay: 0,
+        },
+    ];
+    client.set_approval_tiers(&vault_id, &s.owner, &tiers, &s.quorum(2));
+    let limits = vec![
+        &s.env,
+        VelocityLimit {
+            period: 86_400,
+            cap: 50 * USD,
+        },
+    ];
+    client.set_velocity_limits(&vault_id, &s.owner, &limits, &s.quorum(2));
+
+    // 5 tokens are $10 and need the base threshold; 10 tokens are $20.
+    client.withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &50_000_000,
+        &s.quorum(2),
+    );
+    let result = client.try_withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &100_000_000,
+        &s.quorum(2),
+    );
+    assert_eq!(fails(result), CustodyError::InsufficientSignatures.into());
+    client.withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &100_000_000,
+        &s.quorum(3),
+    );
+
+    // $30 spent today; another $21 breaks the $50 cap.
+    let result = client.try_withdraw_assets(
+        &vault_id,
+        &s.owner,
+        &s.token,
+        &s.dest,
+        &105_000_000,
+        &s.quorum(3),
+    );
+    assert_eq!(fails(result), CustodyError::VelocityLimitExceeded.into());
+
+    s.env.ledger().with_mut(|l| l.timestamp = 1_301);
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::PriceStale.into());
+
+    feed.set_price(&0, &1_301);
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &1, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::PriceUnavailable.into());
+    assert_eq!(s.token_balance(&s.dest), 150_000_000);
+}
+
+#[test]
+fn proposals_are_priced_when_they_execute() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &1_000_000);
+    let tiers = vec![
+        &s.env,
+        ApprovalTier {
+            min_amount: 20 * USD,
+            threshold: 3,
+            delay: 0,
+        },
+    ];
+    client.set_approval_tiers(&vault_id, &s.owner, &tiers, &s.quorum(2));
+
+    // 0.05 tokens in raw units stay far below the tier, so two approvals queue it.
+    let id = client.propose_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &500_000);
+    client.approve_withdrawal(&id, &s.signers.get(0).unwrap());
+    client.approve_withdrawal(&id, &s.signers.get(1).unwrap());
+
+    // At $500 per token the same amount is $25 and needs the third signer.
+    let oracle = s.env.register(MockOracle, ());
+    let feed = MockOracleClient::new(&s.env, &oracle);
+    feed.set_price(&(500 * USD), &0);
+    assert_eq!(
+        fails(client.try_set_price_oracle(
+            &vault_id,
+            &s.owner,
+            &Some(oracle.clone()),
+            &300,
+            &s.quorum(2)
+        )),
+        CustodyError::InsufficientSignatures.into()
+    );
+    client.set_price_oracle(&vault_id, &s.owner, &Some(oracle), &300, &s.quorum(3));
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::InsufficientApprovals.into()
+    );
+
+    s.env.ledger().with_mut(|l| l.timestamp = 301);
+    assert_eq!(
+        fails(client.try_approve_withdrawal(&id, &s.signers.get(2).unwrap())),
+        OracleError::PriceStale.into()
+    );
+    // Revoking works without a price and takes the proposal out of the queue.
+    client.revoke_approval(&id, &s.signers.get(1).unwrap());
+    assert_eq!(client.view_proposal(&id, &s.owner).queued_at, None);
+
+    feed.set_price(&(500 * USD), &301);
+    client.approve_withdrawal(&id, &s.signers.get(1).unwrap());
+    client.approve_withdrawal(&id, &s.signers.get(2).unwrap());
+    client.execute_withdrawal(&id);
+    assert_eq!(s.token_balance(&s.dest), 500_000);
+}
+
+#[test]
+fn the_delay_is_priced_when_a_proposal_executes() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &1_000_000);
+    let tiers = vec![
+        &s.env,
+        ApprovalTier {
+            min_amount: 20 * USD,
+            threshold: 2,
+            delay: 3_600,
+        },
+    ];
+    client.set_approval_tiers(&vault_id, &s.owner, &tiers, &s.quorum(2));
+
+    // Priced in raw units the amount is queued without a delay.
+    let id = client.propose_withdrawal(&vault_id, &s.owner, &s.token, &s.dest, &500_000);
+    client.approve_withdrawal(&id, &s.signers.get(0).unwrap());
+    client.approve_withdrawal(&id, &s.signers.get(1).unwrap());
+
+    // At $500 per token it is $25, which has to wait out the tier delay.
+    let oracle = s.env.register(MockOracle, ());
+    let feed = MockOracleClient::new(&s.env, &oracle);
+    feed.set_price(&(500 * USD), &0);
+    client.set_price_oracle(&vault_id, &s.owner, &Some(oracle), &300, &s.quorum(2));
+    assert_eq!(
+        fails(client.try_execute_withdrawal(&id)),
+        CustodyError::WithdrawalLocked.into()
+    );
+
+    s.env.ledger().with_mut(|l| l.timestamp = 3_600);
+    feed.set_price(&(500 * USD), &3_600);
+    client.execute_withdrawal(&id);
+    assert_eq!(s.token_balance(&s.dest), 500_000);
+}
+
+#[test]
+fn oversized_prices_fail_instead_of_wrapping() {
+    let s = Setup::new();
+    let client = s.client();
+    let vault_id = s.vault();
+    client.deposit_assets(&vault_id, &s.token, &100);
+
+    let oracle = s.env.register(MockOracle, ());
+    let feed = MockOracleClient::new(&s.env, &oracle);
+    client.set_price_oracle(&vault_id, &s.owner, &Some(oracle), &u64::MAX, &s.quorum(2));
+
+    feed.set_price(&USD, &1);
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::PriceOverflow.into());
+
+    feed.set_price(&i128::MAX, &0);
+    let result =
+        client.try_withdraw_assets(&vault_id, &s.owner, &s.token, &s.dest, &10, &s.quorum(2));
+    assert_eq!(fails(result), OracleError::PriceOverflow.into());
+    assert_eq!(s.token_balance(&s.dest), 0);
+}